@@ -0,0 +1,79 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::spawner::SwarmSize;
+use crate::swarm::SwarmId;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BeaconConfig {
+    /// ticks between beacon spawns while below max_beacons (0 = no beacons)
+    pub spawn_interval: u32,
+
+    /// maximum number of beacons in the arena at the same time
+    pub max_beacons: u32,
+
+    /// ticks until an uncaptured beacon disappears (0 = never)
+    pub lifetime: u32,
+
+    /// number of ships a captured beacon adds to the swarm
+    pub payload: SwarmSize,
+
+    /// minimum distance of spawned beacons to the walls
+    pub margin: f32,
+
+    /// swarm center must stay within this distance to capture a beacon
+    pub capture_radius: f32,
+
+    /// ticks a swarm has to hold a beacon to capture it
+    pub capture_ticks: u32,
+
+    /// lock time multiplier for swarms capturing a beacon (1.0 = no penalty)
+    pub capture_lock_factor: f32,
+}
+
+impl Default for BeaconConfig {
+    fn default() -> Self {
+        BeaconConfig {
+            spawn_interval: 600,
+            max_beacons: 2,
+            lifetime: 1800,
+            payload: SwarmSize::Uniform { min: 1, max: 5 },
+            margin: 100.0,
+            capture_radius: 60.0,
+            capture_ticks: 120,
+            capture_lock_factor: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BeaconId(pub u64);
+
+/// Progress of a swarm capturing a beacon
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Capture {
+    pub swarm: SwarmId,
+    /// ticks the swarm has held the beacon without interruption
+    pub progress: u32,
+}
+
+/// Regeneration pickup, adds ships to the swarm that captures it.
+/// Beacons are used up on capture and disappear after their lifetime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Beacon {
+    pub id: BeaconId,
+    pub pos: Vec2,
+    /// number of ships added on capture
+    pub payload: u32,
+    /// tick the beacon disappears at if nobody captures it
+    pub expires_at: Option<u64>,
+    /// swarm currently capturing the beacon, the nearest one within capture_radius
+    pub capture: Option<Capture>,
+}
+
+impl Beacon {
+    pub fn is_captured_by(&self, swarm: SwarmId) -> bool {
+        self.capture.is_some_and(|capture| capture.swarm == swarm)
+    }
+}
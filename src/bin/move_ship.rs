@@ -1,14 +1,14 @@
 use macroquad::prelude::*;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use swarm_simulation::render::draw_ship;
-use swarm_simulation::ship::{Ship, ShipConfig};
+use swarm_simulation::ship::{Ship, ShipConfig, ShipId};
 
 #[macroquad::main("Move Ship")]
 async fn main() {
-    let config = Rc::new(ShipConfig::default());
+    let config = Arc::new(ShipConfig::default());
     let start_pos = Vec2::new(screen_width() / 2.0, screen_height() / 2.0);
-    let mut ship = Ship::spawn(start_pos, config);
+    let mut ship = Ship::spawn(ShipId(0), start_pos, config);
 
     loop {
         // Handle mouse click to set target
@@ -18,7 +18,7 @@ async fn main() {
         }
 
         // Update ship
-        ship.movement(1.0);
+        ship.movement(1.0, 1.0);
 
         // Render
         clear_background(WHITE);
@@ -1,17 +1,28 @@
 use macroquad::prelude::*;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use swarm_simulation::render::draw_swarm;
-use swarm_simulation::ship::ShipConfig;
-use swarm_simulation::swarm::{Swarm, SwarmConfig};
+use swarm_simulation::ship::{ShipConfig, ShipIdAllocator};
+use swarm_simulation::simulation::Bounds;
+use swarm_simulation::swarm::{Swarm, SwarmConfig, SwarmId};
 
 #[macroquad::main("Move Swarm")]
 async fn main() {
-    let ship_config = Rc::new(ShipConfig::default());
-    let swarm_config = Rc::new(SwarmConfig::default());
+    let ship_config = Arc::new(ShipConfig::default());
+    let swarm_config = Arc::new(SwarmConfig::default());
 
+    let bounds = Bounds::new(screen_width(), screen_height());
     let start_pos = Vec2::new(screen_width() / 2.0, screen_height() / 2.0);
-    let mut swarm = Swarm::spawn(start_pos, 30, swarm_config, ship_config);
+    let mut ship_ids = ShipIdAllocator::default();
+    let mut swarm = Swarm::spawn(
+        SwarmId(0),
+        start_pos,
+        30,
+        swarm_config.personality.midpoint(),
+        &mut ship_ids,
+        swarm_config,
+        ship_config,
+    );
 
     loop {
         if is_mouse_button_pressed(MouseButton::Left) {
@@ -19,7 +30,7 @@ async fn main() {
             swarm.set_target(Vec2::new(mx, my));
         }
 
-        swarm.movement();
+        swarm.movement(&bounds, &mut Vec::new());
 
         clear_background(WHITE);
         draw_swarm(&swarm, BLUE);
@@ -1,15 +1,27 @@
+use std::collections::{HashMap, VecDeque};
+
 use macroquad::miniquad::conf::Platform;
 use macroquad::prelude::*;
 
 use macroquad_viewplane_camera::ViewplaneCamera;
 
-use swarm_simulation::render::{draw_background_cover, draw_swarm};
-use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::event::{DestroyCause, EventKind};
+use swarm_simulation::render::{
+    draw_background_cover, draw_beacon, draw_obstacle, draw_swarm, draw_zone,
+};
+use swarm_simulation::replay::{Replay, ReplayPlayer, ReplayRecorder};
+use swarm_simulation::scenario::Scenario;
+use swarm_simulation::simulation::Simulation;
+use swarm_simulation::snapshot::SimulationSnapshot;
+use swarm_simulation::swarm::{Swarm, SwarmId, TeamId};
 
-const NUM_SWARMS: usize = 15;
-const MAP_WIDTH: f32 = 1980.;
-const MAP_HEIGHT: f32 = 1980.;
 const SIM_FRAME_TIME: f64 = 1. / 60.;
+const KILL_FEED_LEN: usize = 8;
+const SNAPSHOT_PATH: &str = "snapshot.bin";
+const REPLAY_PATH: &str = "replay.bin";
+const MIN_REPLAY_SPEED: f64 = 0.25;
+const MAX_REPLAY_SPEED: f64 = 8.0;
+const TIMELINE_HEIGHT: f32 = 24.0;
 
 fn window_conf() -> Conf {
     Conf {
@@ -22,79 +34,147 @@ fn window_conf() -> Conf {
     }
 }
 
-fn generate_colors(n: usize) -> Vec<Color> {
-    (0..n)
-        .map(|_| {
-            Color::new(
-                rand::gen_range(0.0, 1.0),
-                rand::gen_range(0.0, 1.0),
-                rand::gen_range(0.0, 1.0),
-                1.0,
-            )
-        })
-        .collect()
-}
-
-fn random_pos(bounds: &Bounds) -> Vec2 {
-    Vec2::new(
-        rand::gen_range(100.0, bounds.max.x - 100.0),
-        rand::gen_range(100.0, bounds.max.y - 100.0),
+fn generate_color() -> Color {
+    Color::new(
+        rand::gen_range(0.0, 1.0),
+        rand::gen_range(0.0, 1.0),
+        rand::gen_range(0.0, 1.0),
+        1.0,
     )
 }
 
-fn random_edge_pos(bounds: &Bounds) -> Vec2 {
-    let edge = rand::gen_range(0, 4);
-    match edge {
-        0 => Vec2::new(rand::gen_range(bounds.min.x, bounds.max.x), bounds.min.y),
-        1 => Vec2::new(rand::gen_range(bounds.min.x, bounds.max.x), bounds.max.y),
-        2 => Vec2::new(bounds.min.x, rand::gen_range(bounds.min.y, bounds.max.y)),
-        _ => Vec2::new(bounds.max.x, rand::gen_range(bounds.min.y, bounds.max.y)),
-    }
-}
+/// Load the scenario at the given path, or a randomly seeded default scenario
+async fn load_scenario(path: Option<String>) -> Scenario {
+    let Some(path) = path else {
+        let mut scenario = Scenario::default();
+        scenario.simulation.seed = miniquad::date::now() as u64;
+        return scenario;
+    };
 
-#[macroquad::main(window_conf)]
-async fn main() {
-    let bounds = Bounds::new(MAP_WIDTH, MAP_HEIGHT);
-    let mut camera = ViewplaneCamera::new(MAP_WIDTH, MAP_HEIGHT);
+    let source = load_string(&path)
+        .await
+        .unwrap_or_else(|err| panic!("failed to read scenario {path}: {err}"));
+    Scenario::from_toml(&source).unwrap_or_else(|err| panic!("{path}: {err}"))
+}
 
-    let mut sim = Simulation::new(SimulationConfig::default(), bounds);
+async fn load_replay(path: &str) -> Replay {
+    let bytes = load_file(path)
+        .await
+        .unwrap_or_else(|err| panic!("failed to read replay {path}: {err}"));
+    Replay::from_bytes(&bytes).unwrap_or_else(|err| panic!("{path}: {err}"))
+}
 
-    let mut colors = generate_colors(NUM_SWARMS);
+/// Rendering state shared by live and replay mode
+struct Viewer {
+    camera: ViewplaneCamera,
+    background: Texture2D,
+    colors: HashMap<SwarmId, Color>,
+    team_colors: HashMap<TeamId, Color>,
+    kill_feed: VecDeque<(String, Color)>,
+}
 
-    let background = load_texture("assets/backgrounds/space_background1.png")
-        .await
-        .unwrap();
+impl Viewer {
+    async fn new(sim: &Simulation) -> Viewer {
+        let bounds = sim.bounds();
+        let background = load_texture("assets/backgrounds/space_background1.png")
+            .await
+            .unwrap();
 
-    for _ in 0..NUM_SWARMS {
-        let num_ships = rand::gen_range(2, 30);
-        sim.spawn_swarm(random_pos(sim.bounds()), num_ships);
+        Viewer {
+            camera: ViewplaneCamera::new(bounds.max.x, bounds.max.y),
+            background,
+            colors: HashMap::new(),
+            team_colors: HashMap::new(),
+            kill_feed: VecDeque::new(),
+        }
     }
 
-    let mut sim_time_lag = 0.0;
+    /// Swarms get a random color on first sight, which is kept for their lifetime
+    fn color(&mut self, swarm: SwarmId) -> Color {
+        *self.colors.entry(swarm).or_insert_with(generate_color)
+    }
 
-    loop {
-        camera.set_viewport(0, 0, screen_width() as i32, screen_height() as i32);
-        camera.handle_inputs();
+    /// Like `color`, but all swarms of a team share their team's color
+    fn swarm_color(&mut self, swarm: &Swarm) -> Color {
+        let Some(team) = swarm.team else {
+            return self.color(swarm.id);
+        };
+        let color = *self.team_colors.entry(team).or_insert_with(generate_color);
+        self.colors.insert(swarm.id, color);
+        color
+    }
 
-        // respawn swarms at map edges if below target count
-        while sim.swarms().len() < NUM_SWARMS {
-            let num_ships = rand::gen_range(2, 30);
-            sim.spawn_swarm(random_edge_pos(sim.bounds()), num_ships);
-            colors.push(generate_colors(1)[0]);
+    fn handle_inputs(&mut self, allow_mouse: bool) {
+        self.camera
+            .set_viewport(0, 0, screen_width() as i32, screen_height() as i32);
+        if allow_mouse {
+            self.camera.handle_inputs();
         }
+    }
 
-        // run simulation steps needed to catch up, but dont exceed target simulation speed
-        sim_time_lag += get_frame_time() as f64;
-        while sim_time_lag >= SIM_FRAME_TIME {
-            sim.step();
-            sim_time_lag -= SIM_FRAME_TIME;
+    /// Add kills and eliminations to the kill feed
+    fn handle_events(&mut self, sim: &mut Simulation) {
+        for event in sim.drain_events() {
+            // split off swarms keep the color of their parent
+            if let EventKind::SwarmSplit {
+                swarm, new_swarm, ..
+            } = event.kind
+            {
+                let color = self.color(swarm);
+                self.colors.insert(new_swarm, color);
+            }
+            let entry = match event.kind {
+                EventKind::ShipDestroyed {
+                    swarm,
+                    cause: DestroyCause::Shot { killer_swarm, .. },
+                    ..
+                } => Some((
+                    format!(
+                        "swarm {} destroyed a ship of swarm {}",
+                        killer_swarm.0, swarm.0
+                    ),
+                    killer_swarm,
+                )),
+                EventKind::ShipDestroyed {
+                    swarm,
+                    cause: cause @ (DestroyCause::Zone | DestroyCause::Walls),
+                    ..
+                } => {
+                    let place = if cause == DestroyCause::Zone {
+                        "zone"
+                    } else {
+                        "walls"
+                    };
+                    Some((
+                        format!("swarm {} lost a ship outside the {place}", swarm.0),
+                        swarm,
+                    ))
+                }
+                EventKind::SwarmEliminated {
+                    swarm,
+                    killer_swarm: Some(killer_swarm),
+                } => Some((
+                    format!("swarm {} eliminated swarm {}", killer_swarm.0, swarm.0),
+                    killer_swarm,
+                )),
+                _ => None,
+            };
+            if let Some((text, swarm)) = entry {
+                let color = self.color(swarm);
+                self.kill_feed.push_back((text, color));
+                if self.kill_feed.len() > KILL_FEED_LEN {
+                    self.kill_feed.pop_front();
+                }
+            }
         }
+    }
 
+    fn draw(&mut self, sim: &Simulation) {
         clear_background(WHITE);
 
-        draw_background_cover(&background, 16. / 9.);
+        draw_background_cover(&self.background, 16. / 9.);
 
-        camera.apply();
+        self.camera.apply();
 
         let bounds = sim.bounds();
         draw_rectangle_lines(
@@ -105,14 +185,206 @@ async fn main() {
             4.0,
             BLACK,
         );
+        for obstacle in bounds.obstacles.iter() {
+            draw_obstacle(obstacle, DARKGRAY);
+        }
+        if let Some(zone) = &bounds.zone {
+            draw_zone(zone, RED);
+        }
 
-        for (i, swarm) in sim.swarms().iter().enumerate() {
-            let color = colors.get(i).copied().unwrap_or(GRAY);
+        for beacon in sim.beacons() {
+            let color = beacon.capture.map(|capture| self.color(capture.swarm));
+            draw_beacon(beacon, &sim.config().beacons, color);
+        }
+
+        for swarm in sim.swarms() {
+            let color = self.swarm_color(swarm);
             draw_swarm(swarm, color);
         }
+        self.colors.retain(|id, _| sim.swarm(*id).is_some());
+
+        self.camera.reset_camera();
+
+        for (i, (text, color)) in self.kill_feed.iter().enumerate() {
+            draw_text(text, 10.0, 20.0 + i as f32 * 18.0, 18.0, *color);
+        }
+    }
+}
+
+/// Run a scenario interactively.
+/// space: pause, F5: save snapshot, F9: restore snapshot, F6: save replay
+async fn run_live(scenario: Scenario) {
+    println!("seed: {}", scenario.simulation.seed);
+
+    let mut sim = scenario.build();
+    let mut recorder = ReplayRecorder::start(&sim);
+    let mut viewer = Viewer::new(&sim).await;
 
-        camera.reset_camera();
+    let mut sim_time_lag = 0.0;
+    let mut paused = false;
+
+    loop {
+        viewer.handle_inputs(true);
+
+        if is_key_pressed(KeyCode::Space) {
+            paused = !paused;
+        }
+        if is_key_pressed(KeyCode::F5) {
+            match sim.snapshot().save(SNAPSHOT_PATH) {
+                Ok(()) => println!("saved snapshot at tick {} to {SNAPSHOT_PATH}", sim.tick()),
+                Err(err) => println!("{err}"),
+            }
+        }
+        if is_key_pressed(KeyCode::F9) {
+            match SimulationSnapshot::load(SNAPSHOT_PATH).and_then(Simulation::from_snapshot) {
+                Ok(restored) => {
+                    sim = restored;
+                    recorder = ReplayRecorder::start(&sim);
+                    viewer.kill_feed.clear();
+                    println!("restored snapshot at tick {}", sim.tick());
+                }
+                Err(err) => println!("{err}"),
+            }
+        }
+        if is_key_pressed(KeyCode::F6) {
+            match recorder
+                .replay(&sim)
+                .and_then(|replay| replay.save(REPLAY_PATH))
+            {
+                Ok(()) => println!("saved replay up to tick {} to {REPLAY_PATH}", sim.tick()),
+                Err(err) => println!("{err}"),
+            }
+        }
+
+        // run simulation steps needed to catch up, but dont exceed target simulation speed
+        if !paused {
+            sim_time_lag += get_frame_time() as f64;
+        }
+        while sim_time_lag >= SIM_FRAME_TIME {
+            sim.step();
+            sim_time_lag -= SIM_FRAME_TIME;
+        }
+
+        viewer.handle_events(&mut sim);
+        viewer.draw(&sim);
 
         next_frame().await;
     }
 }
+
+/// Play back a recorded replay.
+/// space: pause, left/right: step one tick, up/down: change speed,
+/// click or drag the timeline to scrub
+async fn run_replay(replay: Replay) {
+    let mut player = ReplayPlayer::new(replay).unwrap_or_else(|err| panic!("{err}"));
+    let mut viewer = Viewer::new(player.sim()).await;
+
+    let mut sim_time_lag = 0.0;
+    let mut paused = false;
+    let mut speed: f64 = 1.0;
+
+    loop {
+        let (mouse_x, mouse_y) = mouse_position();
+        let over_timeline = mouse_y >= screen_height() - TIMELINE_HEIGHT;
+        viewer.handle_inputs(!over_timeline);
+
+        if is_key_pressed(KeyCode::Space) {
+            paused = !paused;
+        }
+        if is_key_pressed(KeyCode::Up) {
+            speed = (speed * 2.0).min(MAX_REPLAY_SPEED);
+        }
+        if is_key_pressed(KeyCode::Down) {
+            speed = (speed / 2.0).max(MIN_REPLAY_SPEED);
+        }
+
+        let mut seek_to = None;
+        if is_key_pressed(KeyCode::Right) {
+            paused = true;
+            seek_to = Some(player.tick() + 1);
+        }
+        if is_key_pressed(KeyCode::Left) {
+            paused = true;
+            seek_to = Some(player.tick().saturating_sub(1));
+        }
+        if over_timeline && is_mouse_button_down(MouseButton::Left) {
+            let replay = player.replay();
+            let progress = (mouse_x / screen_width()).clamp(0.0, 1.0) as f64;
+            let offset = (replay.ticks as f64 * progress).round() as u64;
+            seek_to = Some(replay.start_tick() + offset);
+        }
+
+        if let Some(tick) = seek_to {
+            let backwards = tick < player.tick();
+            if let Err(err) = player.seek(tick) {
+                println!("{err}");
+            }
+            sim_time_lag = 0.0;
+            if backwards {
+                // events of the re-simulated ticks were already shown
+                player.sim_mut().drain_events();
+                viewer.kill_feed.clear();
+            }
+        }
+
+        if !paused {
+            sim_time_lag += get_frame_time() as f64 * speed;
+        }
+        while sim_time_lag >= SIM_FRAME_TIME {
+            player.step();
+            sim_time_lag -= SIM_FRAME_TIME;
+        }
+
+        viewer.handle_events(player.sim_mut());
+        viewer.draw(player.sim());
+        draw_timeline(&player, paused, speed);
+
+        next_frame().await;
+    }
+}
+
+fn draw_timeline(player: &ReplayPlayer, paused: bool, speed: f64) {
+    let replay = player.replay();
+    let progress = (player.tick() - replay.start_tick()) as f32 / replay.ticks.max(1) as f32;
+    let y = screen_height() - TIMELINE_HEIGHT;
+
+    draw_rectangle(
+        0.0,
+        y,
+        screen_width(),
+        TIMELINE_HEIGHT,
+        BLACK.with_alpha(0.5),
+    );
+    draw_rectangle(
+        0.0,
+        y,
+        screen_width() * progress,
+        TIMELINE_HEIGHT,
+        WHITE.with_alpha(0.5),
+    );
+
+    let status = match player.verify() {
+        Some(true) => "end (verified)",
+        Some(false) => "end (diverged from recording!)",
+        None if paused => "paused",
+        None => "playing",
+    };
+    let text = format!(
+        "tick {} / {}   {speed}x   {status}",
+        player.tick(),
+        replay.end_tick()
+    );
+    draw_text(&text, 10.0, y - 8.0, 20.0, WHITE);
+}
+
+#[macroquad::main(window_conf)]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    match args.next() {
+        Some(flag) if flag == "--replay" => {
+            let path = args.next().unwrap_or_else(|| REPLAY_PATH.to_owned());
+            run_replay(load_replay(&path).await).await;
+        }
+        path => run_live(load_scenario(path).await).await,
+    }
+}
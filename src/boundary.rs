@@ -0,0 +1,50 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// What happens to ships that cross the arena walls. Ignored on a torus,
+/// ships wrap around there instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Boundary {
+    /// the walls do nothing, ships may fly past them
+    Open,
+    /// ships stop at the walls, keeping only their velocity along them
+    #[default]
+    Clamp,
+    /// ships bounce off the walls without losing speed
+    Bounce,
+    /// ships may leave the arena, but lose `damage` health every `interval`
+    /// ticks while outside
+    Damage { interval: u32, damage: u32 },
+}
+
+impl Boundary {
+    /// Applies the walls from `min` to `max` to a ship at `pos` moving with
+    /// `vel`. Returns its new position and velocity and whether it touched
+    /// or is outside the walls.
+    pub fn apply(self, min: Vec2, max: Vec2, pos: Vec2, vel: Vec2) -> (Vec2, Vec2, bool) {
+        let below = pos.cmplt(min);
+        let above = pos.cmpgt(max);
+        let outside = below | above;
+        if !outside.any() {
+            return (pos, vel, false);
+        }
+        match self {
+            Boundary::Open => (pos, vel, false),
+            Boundary::Clamp => {
+                let vel = Vec2::select(outside, Vec2::ZERO, vel);
+                (pos.clamp(min, max), vel, true)
+            }
+            Boundary::Bounce => {
+                // mirror the overshoot back into the arena
+                let pos = Vec2::select(below, 2.0 * min - pos, pos);
+                let pos = Vec2::select(above, 2.0 * max - pos, pos);
+                // only turn around ships still heading out, not those already on their way back
+                let heading_out = (below & vel.cmplt(Vec2::ZERO)) | (above & vel.cmpgt(Vec2::ZERO));
+                let vel = Vec2::select(heading_out, -vel, vel);
+                (pos.clamp(min, max), vel, true)
+            }
+            Boundary::Damage { .. } => (pos, vel, true),
+        }
+    }
+}
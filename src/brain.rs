@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use glam::Vec2;
+
+use crate::observation::{Ally, Contact, Observation, RememberedContact};
+use crate::repulsion::RepulsionMap;
+use crate::swarm::{Intent, SwarmDecision, SwarmId};
+
+/// Estimated ticks until a pursuer moving at `velocity` intercepts prey `offset`
+/// away from it. Assumes the prey keeps its velocity and the pursuer flies
+/// straight at max_speed once it got up to speed.
+/// None if the prey can't be caught at all.
+fn intercept_time(
+    offset: Vec2,
+    prey_velocity: Vec2,
+    velocity: Vec2,
+    max_speed: f32,
+    max_accel: f32,
+) -> Option<f32> {
+    // solve |offset + prey_velocity * t| = max_speed * t for the smallest t >= 0
+    let a = prey_velocity.length_squared() - max_speed * max_speed;
+    let b = 2.0 * offset.dot(prey_velocity);
+    let c = offset.length_squared();
+    let t = if a.abs() < f32::EPSILON {
+        // prey as fast as we are, only catchable when it comes towards us
+        (b < 0.0).then(|| -c / b)?
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+            .into_iter()
+            .filter(|t| *t >= 0.0)
+            .min_by(f32::total_cmp)?
+    };
+
+    // time lost getting up to speed in the intercept direction
+    let heading = (offset + prey_velocity * t).normalize_or_zero();
+    let initial_speed = velocity.dot(heading).clamp(-max_speed, max_speed);
+    let ramp_up = (max_speed - initial_speed).powi(2) / (2.0 * max_accel * max_speed);
+    Some(t + ramp_up)
+}
+
+/// Decision making strategy of a swarm. Brains are stateless and only see
+/// the swarm's observation, so one brain can be shared by many swarms and
+/// simulations stay reproducible. Brains may decide for several swarms at
+/// once on different threads, see `Simulation::set_parallel`.
+pub trait SwarmBrain: Send + Sync {
+    /// Name of the strategy, for display and logs
+    fn name(&self) -> &str;
+
+    /// Decide where the observing swarm should go next, None keeps its current target
+    fn decide(&self, observation: &Observation) -> Option<SwarmDecision>;
+}
+
+/// Picks the brain of every newly spawned swarm, see `Simulation::set_brain_factory`
+pub type BrainFactory = Arc<dyn Fn(SwarmId) -> Arc<dyn SwarmBrain> + Send + Sync>;
+
+/// Brains by `SwarmBrain::name` and the brain factory, to give a restored
+/// simulation its brains back. Snapshots only store brain names.
+#[derive(Clone)]
+pub struct BrainRegistry {
+    brains: HashMap<String, Arc<dyn SwarmBrain>>,
+    factory: Option<BrainFactory>,
+}
+
+impl Default for BrainRegistry {
+    /// Only knows the default brain
+    fn default() -> Self {
+        let mut registry = BrainRegistry {
+            brains: HashMap::new(),
+            factory: None,
+        };
+        registry.register(Arc::new(RepulsionBrain));
+        registry
+    }
+}
+
+impl BrainRegistry {
+    /// Restore swarms whose brain had the name of `brain` with `brain`
+    pub fn register(&mut self, brain: Arc<dyn SwarmBrain>) {
+        self.brains.insert(brain.name().to_string(), brain);
+    }
+
+    /// Brain factory for simulations that had one when the snapshot was taken
+    pub fn set_factory(
+        &mut self,
+        factory: impl Fn(SwarmId) -> Arc<dyn SwarmBrain> + Send + Sync + 'static,
+    ) {
+        self.factory = Some(Arc::new(factory));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn SwarmBrain>> {
+        self.brains.get(name).cloned()
+    }
+
+    pub fn factory(&self) -> Option<&BrainFactory> {
+        self.factory.as_ref()
+    }
+}
+
+/// Distance to the walls at which fleeing swarms start to avoid them
+const WALL_DETECT_RANGE: f32 = 150.0;
+/// Targets are kept at least this far from the walls
+const WALL_MARGIN: f32 = 50.0;
+/// Hover targets closer than this to the current one are ignored
+const HOVER_TOLERANCE: f32 = 20.0;
+/// Prey that can't be intercepted within this many ticks is ignored
+const CHASE_HORIZON: f32 = 300.0;
+/// Attraction towards fleeing allies
+const ALLY_PULL: f32 = 0.5;
+/// Allies heading this close to prey are hunting it
+const FOCUS_RADIUS: f32 = 150.0;
+/// Prey score bonus per ally hunting it
+const FOCUS_BONUS: f32 = 0.5;
+/// Only kite threats this much slower than us
+const KITE_SPEED_MARGIN: f32 = 1.1;
+/// How far ahead on the kiting circle to aim, in radians
+const ORBIT_LEAD: f32 = 0.6;
+/// Pick a new wander target this close to the current one
+const ROAM_ARRIVAL: f32 = 50.0;
+/// Never seen regions count as seen this many ticks ago
+const UNEXPLORED_STALENESS: u64 = 3600;
+
+/// Default brain: flees from threats using a repulsion map, hovers around
+/// evenly matched enemies and hunts prey or captures beacons otherwise.
+/// All thresholds come from the swarm's personality.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RepulsionBrain;
+
+impl SwarmBrain for RepulsionBrain {
+    fn name(&self) -> &str {
+        "repulsion"
+    }
+
+    /// Flee from threats, kite a lone slower one, hover around evenly matched
+    /// enemies, then hunt prey or capture beacons. With nothing to do, head
+    /// for remembered beacons or roam towards stale parts of the arena.
+    fn decide(&self, observation: &Observation) -> Option<SwarmDecision> {
+        let situation = Situation::new(observation);
+        let swarm = &observation.own;
+        let contacts = situation.classify_contacts();
+
+        if !contacts.threats.is_empty() {
+            return situation
+                .kite(&contacts.threats)
+                .or_else(|| Some(situation.flee(&contacts.threats)));
+        }
+        if let Some(enemy) = contacts.hover_from {
+            return situation.hover(enemy);
+        }
+
+        // less aggressive swarms regenerate before hunting, the smaller they are the more likely
+        let beacon = observation
+            .beacons
+            .first()
+            .filter(|_| swarm.num_ships < swarm.max_ships)
+            .map(|beacon| beacon.pos);
+        let prefers_beacon = swarm.occupancy < 1.0 - swarm.personality.aggression;
+        let target = match (contacts.prey, beacon) {
+            (Some(_), Some(beacon)) if prefers_beacon => Some(beacon),
+            (Some(prey), _) => Some(prey),
+            (None, beacon) => beacon,
+        };
+
+        // nothing to do here, go looking for something
+        let target = target.or_else(|| {
+            if swarm.roam_interval == 0 {
+                return None;
+            }
+            situation
+                .remembered_beacon_target()
+                .or_else(|| situation.roam())
+        });
+
+        // keep a reached beacon as target, re-targeting on arrival would spin the formation
+        target
+            .filter(|pos| *pos != swarm.target_pos)
+            .map(|target| SwarmDecision {
+                target,
+                intent: Intent::Move,
+            })
+    }
+}
+
+/// Threat that is out of sight, where we expect it and how sure we are
+struct RememberedThreat<'a> {
+    memory: &'a RememberedContact,
+    pos: Vec2,
+    confidence: f32,
+}
+
+/// Nearby swarms sorted by how the swarm reacts to them
+struct Contacts<'a> {
+    threats: Vec<&'a Contact>,
+    /// first evenly matched enemy
+    hover_from: Option<&'a Contact>,
+    /// intercept point of the best prey
+    prey: Option<Vec2>,
+}
+
+/// What the repulsion brain derives from an observation before deciding
+struct Situation<'a> {
+    observation: &'a Observation,
+    own_ships: f32,
+    threat_range: f32,
+    /// allies within vision range, empty without coordination
+    nearby_allies: Vec<&'a Ally>,
+    remembered_threats: Vec<RememberedThreat<'a>>,
+}
+
+impl<'a> Situation<'a> {
+    fn new(observation: &'a Observation) -> Self {
+        let swarm = &observation.own;
+        let personality = &swarm.personality;
+        let own_ships = swarm.num_ships.max(1) as f32;
+        let nearby_allies = observation
+            .allies
+            .iter()
+            .filter(|ally| swarm.coordination && ally.distance <= swarm.vision_range)
+            .collect();
+        let remembered_threats = observation
+            .remembered
+            .iter()
+            .filter(|memory| memory.num_ships as f32 / own_ships >= personality.flee_ratio)
+            .map(|memory| RememberedThreat {
+                memory,
+                pos: observation.estimated_pos(memory),
+                confidence: observation.confidence(memory),
+            })
+            .collect();
+        Situation {
+            observation,
+            own_ships,
+            threat_range: swarm.vision_range * (1.0 - personality.risk_tolerance),
+            nearby_allies,
+            remembered_threats,
+        }
+    }
+
+    /// Sort visible swarms into threats, evenly matched enemies and prey.
+    /// Swarms close to nearby allies are threats even when out of threat range.
+    fn classify_contacts(&self) -> Contacts<'a> {
+        let personality = &self.observation.own.personality;
+        let mut contacts = Contacts {
+            threats: Vec::new(),
+            hover_from: None,
+            prey: None,
+        };
+        let mut best_score = 0.0;
+        for contact in &self.observation.contacts {
+            let size_ratio = contact.num_ships as f32 / self.own_ships;
+            let endangers_ally = self
+                .nearby_allies
+                .iter()
+                .any(|ally| ally.center.distance(contact.center) <= self.threat_range);
+            if size_ratio >= personality.flee_ratio
+                && (contact.distance <= self.threat_range || endangers_ally)
+            {
+                contacts.threats.push(contact);
+            } else if size_ratio > personality.engage_ratio {
+                contacts.hover_from.get_or_insert(contact);
+            } else if let Some((intercept, score)) = self.score_prey(contact, size_ratio)
+                && score > best_score
+            {
+                best_score = score;
+                contacts.prey = Some(intercept);
+            }
+        }
+        contacts
+    }
+
+    /// Intercept point and score of prey, by size advantage, how fast it can
+    /// be intercepted and how many allies hunt it. None if it can't be caught
+    /// in time or a remembered threat guards the intercept point.
+    fn score_prey(&self, contact: &Contact, size_ratio: f32) -> Option<(Vec2, f32)> {
+        let swarm = &self.observation.own;
+        let time = intercept_time(
+            contact.center - swarm.center,
+            contact.velocity,
+            swarm.velocity,
+            swarm.max_speed,
+            swarm.max_accel,
+        )
+        .filter(|time| *time <= CHASE_HORIZON)?;
+        let intercept = self
+            .observation
+            .bounds
+            .clamp_with_margin(contact.center + contact.velocity * time, WALL_MARGIN);
+        // don't chase prey into a threat we just lost sight of
+        let guarded = self
+            .remembered_threats
+            .iter()
+            .any(|threat| threat.pos.distance(intercept) < swarm.vision_range * threat.confidence);
+        if guarded {
+            return None;
+        }
+        let advantage = 1.0 / size_ratio;
+        let catchability = 1.0 - time / CHASE_HORIZON;
+        let hunters = self
+            .nearby_allies
+            .iter()
+            .filter(|ally| ally.intent != Intent::Flee)
+            .filter(|ally| {
+                ally.target_pos.distance(intercept) <= FOCUS_RADIUS
+                    || ally.target_pos.distance(contact.center) <= FOCUS_RADIUS
+            })
+            .count();
+        let focus = 1.0 + FOCUS_BONUS * hunters as f32;
+        Some((intercept, advantage * catchability * focus))
+    }
+
+    /// Harass a lone, slower threat from the edge of aim range instead of
+    /// running: circle it at kite distance. None if running is safer.
+    fn kite(&self, threats: &[&Contact]) -> Option<SwarmDecision> {
+        let swarm = &self.observation.own;
+        let bounds = &self.observation.bounds;
+        let [threat] = threats else {
+            return None;
+        };
+        if !self.remembered_threats.is_empty()
+            || swarm.kite_distance <= 0.0
+            || swarm.max_speed < threat.max_speed * KITE_SPEED_MARGIN
+        {
+            return None;
+        }
+        let from_threat = swarm.center - threat.center;
+        let bearing = from_threat.to_angle();
+        // keep circling in the direction we are already moving, turn around at walls
+        let mut direction = if from_threat.perp_dot(swarm.velocity) >= 0.0 {
+            1.0
+        } else {
+            -1.0
+        };
+        let orbit_point = |direction: f32| {
+            threat.center + Vec2::from_angle(bearing + direction * ORBIT_LEAD) * swarm.kite_distance
+        };
+        let clamped = |pos: Vec2| bounds.clamp_with_margin(pos, WALL_MARGIN);
+        if clamped(orbit_point(direction)) != orbit_point(direction) {
+            direction = -direction;
+        }
+        Some(SwarmDecision {
+            target: clamped(orbit_point(direction)),
+            intent: Intent::Standoff {
+                enemy: threat.swarm,
+                distance: swarm.kite_distance,
+            },
+        })
+    }
+
+    /// Run in the direction the repulsion map likes best: away from visible
+    /// and remembered threats and the walls, along with fleeing allies.
+    fn flee(&self, threats: &[&Contact]) -> SwarmDecision {
+        let swarm = &self.observation.own;
+        let personality = &swarm.personality;
+        let bounds = &self.observation.bounds;
+        let mut repulsion = RepulsionMap::new();
+
+        for enemy in threats {
+            let angle = (enemy.center - swarm.center).to_angle();
+            // scale strength by ship count ratio and inverse distance TODO: is this good?
+            let ship_ratio = enemy.num_ships as f32 / self.own_ships;
+            // threats close to nearby allies are just as dangerous to the group
+            let distance = self
+                .nearby_allies
+                .iter()
+                .map(|ally| ally.center.distance(enemy.center))
+                .fold(enemy.distance, f32::min);
+            let dist_factor = 1.0 - (distance / swarm.vision_range).min(1.0);
+            let strength = ship_ratio * dist_factor;
+            repulsion.add_repulsor(angle, strength, personality.enemy_sigma);
+        }
+        for threat in &self.remembered_threats {
+            let angle = (threat.pos - swarm.center).to_angle();
+            let ship_ratio = threat.memory.num_ships as f32 / self.own_ships;
+            // remembered threats are out of sight, so fade out over twice the vision range
+            let dist = threat.pos.distance(swarm.center);
+            let dist_factor = 1.0 - (dist / (2.0 * swarm.vision_range)).min(1.0);
+            let strength = ship_ratio * dist_factor * threat.confidence;
+            repulsion.add_repulsor(angle, strength, personality.enemy_sigma);
+        }
+        // head where fleeing allies are heading
+        for ally in self
+            .nearby_allies
+            .iter()
+            .filter(|ally| ally.intent == Intent::Flee)
+        {
+            let angle = (ally.target_pos - swarm.center).to_angle();
+            let dist_factor = 1.0 - (ally.distance / swarm.vision_range).min(1.0);
+            repulsion.add_attractor(angle, ALLY_PULL * dist_factor, personality.enemy_sigma);
+        }
+        repulsion.add_wall_repulsion(
+            swarm.center,
+            bounds,
+            WALL_DETECT_RANGE,
+            personality.wall_sigma,
+        );
+        repulsion.add_velocity_penalty(
+            swarm.velocity,
+            personality.velocity_penalty,
+            personality.velocity_sigma,
+        );
+
+        let flee_dir = Vec2::from_angle(repulsion.best_angle());
+        SwarmDecision {
+            target: bounds.clamp_with_margin(
+                swarm.center + flee_dir * personality.flee_distance,
+                WALL_MARGIN,
+            ),
+            intent: Intent::Flee,
+        }
+    }
+
+    /// Stay at hover distance from an evenly matched enemy
+    fn hover(&self, enemy: &Contact) -> Option<SwarmDecision> {
+        let swarm = &self.observation.own;
+        let away = (swarm.center - enemy.center).normalize_or_zero();
+        let target = self.observation.bounds.clamp_with_margin(
+            enemy.center + away * swarm.personality.hover_distance,
+            WALL_MARGIN,
+        );
+        (target.distance(swarm.target_pos) > HOVER_TOLERANCE).then_some(SwarmDecision {
+            target,
+            intent: Intent::Move,
+        })
+    }
+
+    /// Nearest beacon out of sight, if the swarm can regenerate
+    fn remembered_beacon_target(&self) -> Option<Vec2> {
+        let swarm = &self.observation.own;
+        self.observation
+            .remembered_beacons
+            .first()
+            .filter(|_| swarm.num_ships < swarm.max_ships)
+            .map(|beacon| beacon.pos)
+    }
+
+    /// The stalest reachable region that is close by and not guarded by a
+    /// remembered threat. Only picks a new one on arrival or every roam interval.
+    fn roam(&self) -> Option<Vec2> {
+        let observation = self.observation;
+        let swarm = &observation.own;
+        let bounds = &observation.bounds;
+        let arrived = swarm.center.distance(swarm.target_pos) <= ROAM_ARRIVAL;
+        let cadence = (observation.tick + swarm.id.0).is_multiple_of(swarm.roam_interval as u64);
+        if !arrived && !cadence {
+            return None;
+        }
+        observation
+            .explored
+            .cells()
+            .filter(|(pos, _)| bounds.is_free(*pos))
+            .map(|(pos, last_seen)| (bounds.nearest_image(swarm.center, pos), last_seen))
+            .filter(|(pos, _)| {
+                !self.remembered_threats.iter().any(|threat| {
+                    threat.pos.distance(*pos) < swarm.vision_range * threat.confidence
+                })
+            })
+            .map(|(pos, last_seen)| {
+                let staleness = last_seen
+                    .map_or(UNEXPLORED_STALENESS, |seen| observation.tick - seen)
+                    .min(UNEXPLORED_STALENESS) as f32;
+                let travel = pos.distance(swarm.center) / swarm.max_speed.max(f32::EPSILON);
+                (pos, staleness - travel)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(pos, _)| bounds.clamp_with_margin(pos, WALL_MARGIN))
+    }
+}
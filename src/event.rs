@@ -0,0 +1,123 @@
+use glam::Vec2;
+
+use crate::beacon::BeaconId;
+use crate::personality::SwarmPersonality;
+use crate::ship::ShipId;
+use crate::swarm::{SwarmId, TeamId};
+
+/// Callback registered with `Simulation::subscribe`
+pub type EventCallback = Box<dyn FnMut(&Event) + Send + Sync>;
+
+/// Something that happened during a simulation step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// tick the event happened in
+    pub tick: u64,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventKind {
+    SwarmSpawned {
+        swarm: SwarmId,
+        pos: Vec2,
+        num_ships: u32,
+        team: Option<TeamId>,
+        personality: SwarmPersonality,
+    },
+    /// a ship started locking onto an enemy ship
+    LockAcquired {
+        swarm: SwarmId,
+        ship: ShipId,
+        target: ShipId,
+    },
+    /// `num_ships` ships of `swarm` left it to form `new_swarm`
+    SwarmSplit {
+        swarm: SwarmId,
+        new_swarm: SwarmId,
+        num_ships: u32,
+    },
+    /// `merged` handed its `num_ships` ships over to `swarm` and is gone
+    SwarmsMerged {
+        swarm: SwarmId,
+        merged: SwarmId,
+        num_ships: u32,
+    },
+    /// a lock was dropped before firing, because the target died or left aim range
+    LockLost {
+        swarm: SwarmId,
+        ship: ShipId,
+        target: ShipId,
+    },
+    ShotFired {
+        swarm: SwarmId,
+        shooter: ShipId,
+        target: ShipId,
+    },
+    DamageApplied {
+        swarm: SwarmId,
+        ship: ShipId,
+        attacker: ShipId,
+        health_left: u32,
+    },
+    ShipDestroyed {
+        swarm: SwarmId,
+        ship: ShipId,
+        cause: DestroyCause,
+    },
+    /// a ship outside the zone lost health, at 0 health left it is destroyed
+    /// with `DestroyCause::Zone`
+    ZoneDamage {
+        swarm: SwarmId,
+        ship: ShipId,
+        health_left: u32,
+    },
+    /// a ship hit the arena walls, or is outside of them with `Boundary::Damage`.
+    /// A ship pinned against a wall reports a contact every tick.
+    WallContact {
+        swarm: SwarmId,
+        ship: ShipId,
+        /// ship position after the walls acted on it
+        pos: Vec2,
+    },
+    /// a ship outside the walls lost health, at 0 health left it is destroyed
+    /// with `DestroyCause::Walls`
+    WallDamage {
+        swarm: SwarmId,
+        ship: ShipId,
+        health_left: u32,
+    },
+    /// the last ship of a swarm was destroyed
+    SwarmEliminated {
+        swarm: SwarmId,
+        /// swarm that destroyed the last ship
+        killer_swarm: Option<SwarmId>,
+    },
+    BeaconSpawned {
+        beacon: BeaconId,
+        pos: Vec2,
+        payload: u32,
+    },
+    /// a swarm held a beacon long enough and received its ships
+    BeaconCaptured {
+        beacon: BeaconId,
+        swarm: SwarmId,
+        ships_added: u32,
+    },
+    /// a beacon disappeared without being captured
+    BeaconExpired { beacon: BeaconId },
+}
+
+/// What took the last health point of a destroyed ship
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DestroyCause {
+    /// shot by `killer` of `killer_swarm`
+    Shot {
+        killer_swarm: SwarmId,
+        killer: ShipId,
+    },
+    /// damage outside the zone
+    Zone,
+    /// damage outside the walls with `Boundary::Damage`
+    Walls,
+}
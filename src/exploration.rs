@@ -0,0 +1,74 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::Bounds;
+
+/// Coarse grid over the arena that tracks when a swarm last saw each cell,
+/// so roaming swarms can head for unexplored or stale regions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExplorationMap {
+    origin: Vec2,
+    cell_size: f32,
+    cols: usize,
+    /// tick each cell was last seen, None if never, row by row
+    last_seen: Vec<Option<u64>>,
+}
+
+impl ExplorationMap {
+    pub fn new(bounds: &Bounds, cell_size: f32) -> ExplorationMap {
+        let size = bounds.max - bounds.min;
+        let cols = (size.x / cell_size).ceil().max(1.0) as usize;
+        let rows = (size.y / cell_size).ceil().max(1.0) as usize;
+        ExplorationMap {
+            origin: bounds.min,
+            cell_size,
+            cols,
+            last_seen: vec![None; cols * rows],
+        }
+    }
+
+    fn cell_center(&self, idx: usize) -> Vec2 {
+        let (col, row) = (idx % self.cols, idx / self.cols);
+        self.origin + (Vec2::new(col as f32, row as f32) + 0.5) * self.cell_size
+    }
+
+    /// Mark all cells with their center within `range` of `pos` as seen at `tick`
+    pub fn mark_seen(&mut self, pos: Vec2, range: f32, tick: u64) {
+        if self.last_seen.is_empty() {
+            return;
+        }
+        let rows = self.last_seen.len() / self.cols;
+        // only the cells overlapping the square around the seen circle
+        let first = ((pos - range - self.origin) / self.cell_size)
+            .floor()
+            .max(Vec2::ZERO);
+        let last = ((pos + range - self.origin) / self.cell_size).floor();
+        let cols = first.x as usize..=(last.x as usize).min(self.cols - 1);
+        let rows = first.y as usize..=(last.y as usize).min(rows - 1);
+
+        let range_sq = range * range;
+        for row in rows {
+            for col in cols.clone() {
+                let idx = row * self.cols + col;
+                if self.cell_center(idx).distance_squared(pos) <= range_sq {
+                    self.last_seen[idx] = Some(tick);
+                }
+            }
+        }
+    }
+
+    /// Take over everything `other` has seen more recently, both maps need the same layout
+    pub fn merge(&mut self, other: &ExplorationMap) {
+        for (own, other) in self.last_seen.iter_mut().zip(&other.last_seen) {
+            *own = (*own).max(*other);
+        }
+    }
+
+    /// Center of every cell and the tick it was last seen
+    pub fn cells(&self) -> impl Iterator<Item = (Vec2, Option<u64>)> + '_ {
+        self.last_seen
+            .iter()
+            .enumerate()
+            .map(|(idx, last_seen)| (self.cell_center(idx), *last_seen))
+    }
+}
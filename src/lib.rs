@@ -1,5 +1,20 @@
+pub mod beacon;
+pub mod boundary;
+pub mod brain;
+pub mod event;
+pub mod exploration;
+pub mod observation;
+pub mod obstacle;
+pub mod personality;
 pub mod render;
+pub mod replay;
 pub mod repulsion;
+pub mod scenario;
 pub mod ship;
 pub mod simulation;
+pub mod snapshot;
+pub mod spatial;
+pub mod spawner;
+pub mod stats;
 pub mod swarm;
+pub mod zone;
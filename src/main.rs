@@ -1,3 +1,304 @@
-fn main() {
-    println!("Hello, world!");
+//! Headless batch runner: runs seeded matches without a window and prints
+//! summary statistics for each run.
+//!
+//! usage: swarm-simulation [--scenario FILE] [--runs N] [--ticks N] [--seed N] [--swarms N] [--events] [--parallel]
+
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+use swarm_simulation::event::{DestroyCause, Event, EventKind};
+use swarm_simulation::personality::SwarmPersonality;
+use swarm_simulation::scenario::Scenario;
+use swarm_simulation::simulation::Simulation;
+use swarm_simulation::stats::SwarmStats;
+use swarm_simulation::swarm::{SwarmId, TeamId};
+
+const USAGE: &str = "usage: swarm-simulation [--scenario FILE] [--runs N] [--ticks N] [--seed N] [--swarms N] [--events] [--parallel]";
+
+struct Args {
+    scenario: Option<String>,
+    runs: u32,
+    ticks: u64,
+    /// overrides the scenario seed
+    seed: Option<u64>,
+    /// overrides init_swarms and max_swarms of the scenario
+    swarms: Option<u32>,
+    /// print spawns, kills and eliminations as they happen
+    events: bool,
+    /// step on all cores, needs the parallel feature
+    parallel: bool,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            scenario: None,
+            runs: 10,
+            ticks: 60 * 60 * 5,
+            seed: None,
+            swarms: None,
+            events: false,
+            parallel: false,
+        }
+    }
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut iter = std::env::args().skip(1);
+
+    while let Some(flag) = iter.next() {
+        if flag == "--events" {
+            args.events = true;
+            continue;
+        }
+        if flag == "--parallel" {
+            if !cfg!(feature = "parallel") {
+                return Err("--parallel needs a build with the parallel feature".to_string());
+            }
+            args.parallel = true;
+            continue;
+        }
+
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        let invalid = |_| format!("invalid value for {flag}: {value}");
+        match flag.as_str() {
+            "--scenario" => args.scenario = Some(value),
+            "--runs" => args.runs = value.parse().map_err(invalid)?,
+            "--ticks" => args.ticks = value.parse().map_err(invalid)?,
+            "--seed" => args.seed = Some(value.parse().map_err(invalid)?),
+            "--swarms" => args.swarms = Some(value.parse().map_err(invalid)?),
+            _ => return Err(format!("unknown argument: {flag}")),
+        }
+    }
+
+    Ok(args)
+}
+
+/// Team and personality of a swarm, kept after it was eliminated
+type SpawnInfo = (Option<TeamId>, SwarmPersonality);
+
+/// Run a single match until the tick budget is used up or at most one side is left.
+/// Also returns the team and personality of all swarms, including eliminated ones.
+fn run_match(scenario: &Scenario, args: &Args) -> (Simulation, HashMap<SwarmId, SpawnInfo>) {
+    let mut sim = scenario.build();
+    if args.parallel {
+        #[cfg(feature = "parallel")]
+        sim.set_parallel(true);
+    }
+    let mut spawns = HashMap::new();
+
+    while sim.tick() < args.ticks && sim.sides_left() > 1 {
+        sim.step();
+        for event in sim.drain_events() {
+            record_spawn(&mut spawns, &event);
+            if args.events {
+                print_event(&event);
+            }
+        }
+    }
+
+    (sim, spawns)
+}
+
+fn record_spawn(spawns: &mut HashMap<SwarmId, SpawnInfo>, event: &Event) {
+    match event.kind {
+        EventKind::SwarmSpawned {
+            swarm,
+            team,
+            personality,
+            ..
+        } => {
+            spawns.insert(swarm, (team, personality));
+        }
+        // split off swarms take after their parent
+        EventKind::SwarmSplit {
+            swarm, new_swarm, ..
+        } => {
+            if let Some(info) = spawns.get(&swarm).copied() {
+                spawns.insert(new_swarm, info);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn print_event(event: &Event) {
+    match &event.kind {
+        EventKind::SwarmSpawned {
+            swarm,
+            num_ships,
+            personality,
+            ..
+        } => println!(
+            "  [{:>6}] swarm {} spawned with {num_ships} ships (aggression {:.2}, risk tolerance {:.2}, engage {:.2}, flee {:.2})",
+            event.tick,
+            swarm.0,
+            personality.aggression,
+            personality.risk_tolerance,
+            personality.engage_ratio,
+            personality.flee_ratio
+        ),
+        EventKind::ShipDestroyed { swarm, ship, cause } => {
+            let cause = match cause {
+                DestroyCause::Shot {
+                    killer_swarm,
+                    killer,
+                } => format!("by ship {} (swarm {})", killer.0, killer_swarm.0),
+                DestroyCause::Zone => "outside the zone".to_string(),
+                DestroyCause::Walls => "outside the walls".to_string(),
+            };
+            println!(
+                "  [{:>6}] ship {} (swarm {}) destroyed {cause}",
+                event.tick, ship.0, swarm.0
+            )
+        }
+        EventKind::SwarmEliminated {
+            swarm,
+            killer_swarm,
+        } => match killer_swarm {
+            Some(killer) => println!(
+                "  [{:>6}] swarm {} eliminated by swarm {}",
+                event.tick, swarm.0, killer.0
+            ),
+            None => println!("  [{:>6}] swarm {} eliminated", event.tick, swarm.0),
+        },
+        EventKind::SwarmSplit {
+            swarm,
+            new_swarm,
+            num_ships,
+        } => println!(
+            "  [{:>6}] swarm {} split off {num_ships} ships as swarm {}",
+            event.tick, swarm.0, new_swarm.0
+        ),
+        EventKind::SwarmsMerged {
+            swarm,
+            merged,
+            num_ships,
+        } => println!(
+            "  [{:>6}] swarm {} merged into swarm {} (+{num_ships} ships)",
+            event.tick, merged.0, swarm.0
+        ),
+        EventKind::BeaconCaptured {
+            beacon,
+            swarm,
+            ships_added,
+        } => println!(
+            "  [{:>6}] swarm {} captured beacon {} (+{ships_added} ships)",
+            event.tick, swarm.0, beacon.0
+        ),
+        _ => {}
+    }
+}
+
+fn print_row(
+    id: SwarmId,
+    status: &str,
+    survivors: u32,
+    stats: &SwarmStats,
+    spawn: Option<&SpawnInfo>,
+) {
+    let team = match spawn {
+        Some((Some(team), _)) => team.0.to_string(),
+        _ => "-".to_string(),
+    };
+    let (aggression, risk) = spawn.map_or((f32::NAN, f32::NAN), |(_, p)| {
+        (p.aggression, p.risk_tolerance)
+    });
+    println!(
+        "  {:>5} {:>4} {:<10} {:>6} {:>6} {:>9} {:>7} {:>6} {:>6} {:>5.2} {:>5.2}",
+        id.0,
+        team,
+        status,
+        stats.spawned_ships,
+        stats.reinforcements,
+        survivors,
+        stats.ticks_alive,
+        stats.kills,
+        stats.shots_fired,
+        aggression,
+        risk
+    );
+}
+
+fn print_summary(run: u32, seed: u64, sim: &Simulation, spawns: &HashMap<SwarmId, SpawnInfo>) {
+    println!(
+        "run {run} (seed {seed}): {} ticks, {} swarms left",
+        sim.tick(),
+        sim.swarms().len()
+    );
+    println!(
+        "  {:>5} {:>4} {:<10} {:>6} {:>6} {:>9} {:>7} {:>6} {:>6} {:>5} {:>5}",
+        "swarm",
+        "team",
+        "status",
+        "ships",
+        "gained",
+        "survivors",
+        "ticks",
+        "kills",
+        "shots",
+        "aggr",
+        "risk"
+    );
+
+    let alive = sim
+        .swarms()
+        .iter()
+        .map(|swarm| (swarm.id, "alive", swarm.num_ships(), &swarm.stats));
+    let eliminated = sim.eliminated().iter().map(|(id, stats)| {
+        let status = match stats.merged_into {
+            Some(_) => "merged",
+            None => "eliminated",
+        };
+        (*id, status, 0, stats)
+    });
+    let mut rows: Vec<_> = alive.chain(eliminated).collect();
+    rows.sort_by_key(|(id, ..)| *id);
+
+    for (id, status, survivors, stats) in rows {
+        print_row(id, status, survivors, stats, spawns.get(&id));
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut scenario = match &args.scenario {
+        Some(path) => match Scenario::load(path) {
+            Ok(scenario) => scenario,
+            Err(err) => {
+                eprintln!("error: {path}: {err}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Scenario::default(),
+    };
+    if let Some(swarms) = args.swarms {
+        scenario.simulation.init_swarms = swarms;
+        scenario.simulation.max_swarms = swarms;
+        if let Err(err) = scenario.validate() {
+            eprintln!("error: --swarms {swarms}: {err}");
+            return ExitCode::FAILURE;
+        }
+    }
+    let base_seed = args.seed.unwrap_or(scenario.simulation.seed);
+
+    for run in 0..args.runs {
+        let seed = base_seed.wrapping_add(run as u64);
+        scenario.simulation.seed = seed;
+        let (sim, spawns) = run_match(&scenario, &args);
+        print_summary(run, seed, &sim, &spawns);
+    }
+
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::beacon::Beacon;
+use crate::exploration::ExplorationMap;
+use crate::personality::SwarmPersonality;
+use crate::ship::ShipId;
+use crate::simulation::Bounds;
+use crate::swarm::{Intent, SwarmId, TeamId};
+
+/// Everything a swarm knows about itself
+#[derive(Debug, Clone)]
+pub struct OwnSwarm {
+    pub id: SwarmId,
+    pub team: Option<TeamId>,
+    pub center: Vec2,
+    pub velocity: Vec2,
+    pub target_pos: Vec2,
+    pub direction: f32,
+    pub num_ships: u32,
+    pub max_ships: u32,
+    /// swarm size relative to max_ships, see `Swarm::occupancy`
+    pub occupancy: f32,
+    pub vision_range: f32,
+    /// ship speed limit after the size penalty
+    pub max_speed: f32,
+    /// ship acceleration limit after the size penalty
+    pub max_accel: f32,
+    /// distance kept when kiting a slower threat (0 = never kite)
+    pub kite_distance: f32,
+    /// ticks a contact is remembered after it was last seen
+    pub memory_ticks: u32,
+    /// ticks between new wander targets while nothing is in sight (0 = no roaming)
+    pub roam_interval: u32,
+    /// whether to hunt and flee together with allies
+    pub coordination: bool,
+    pub personality: SwarmPersonality,
+}
+
+/// Another swarm within vision range
+#[derive(Debug, Clone)]
+pub struct Contact {
+    pub swarm: SwarmId,
+    /// observed center, including observation noise
+    pub center: Vec2,
+    pub velocity: Vec2,
+    pub num_ships: u32,
+    /// ship speed limit after the size penalty
+    pub max_speed: f32,
+    /// distance between our center and the observed center
+    pub distance: f32,
+}
+
+/// Another swarm of the same team, allies always know about each other
+#[derive(Debug, Clone)]
+pub struct Ally {
+    pub swarm: SwarmId,
+    pub center: Vec2,
+    pub velocity: Vec2,
+    pub num_ships: u32,
+    /// where the ally is heading
+    pub target_pos: Vec2,
+    /// what the ally is currently doing
+    pub intent: Intent,
+    pub distance: f32,
+}
+
+/// Last sighting of a swarm that is no longer in vision range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RememberedContact {
+    pub swarm: SwarmId,
+    /// last seen center
+    pub pos: Vec2,
+    /// last seen velocity
+    pub velocity: Vec2,
+    pub num_ships: u32,
+    /// tick of the last sighting
+    pub last_seen: u64,
+}
+
+impl RememberedContact {
+    fn from_contact(contact: &Contact, tick: u64) -> RememberedContact {
+        RememberedContact {
+            swarm: contact.swarm,
+            pos: contact.center,
+            velocity: contact.velocity,
+            num_ships: contact.num_ships,
+            last_seen: tick,
+        }
+    }
+}
+
+/// A single ship of another swarm within vision range
+#[derive(Debug, Clone)]
+pub struct ShipSighting {
+    pub id: ShipId,
+    pub swarm: SwarmId,
+    /// observed position, including observation noise
+    pub pos: Vec2,
+    pub vel: Vec2,
+}
+
+/// What a single swarm can see, built by `Simulation::observe`.
+/// Brains only get an observation, so they can't use information
+/// outside of their team's vision range.
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub tick: u64,
+    pub own: OwnSwarm,
+    /// arena walls, the arena layout is known to everybody
+    pub bounds: Bounds,
+    /// enemy swarms in vision range of the swarm or its allies, nearest first
+    pub contacts: Vec<Contact>,
+    /// swarms of the same team, nearest first
+    pub allies: Vec<Ally>,
+    /// enemy ships in vision range of the swarm or its allies
+    pub ships: Vec<ShipSighting>,
+    /// beacons in vision range of the swarm or its allies, nearest first
+    pub beacons: Vec<Beacon>,
+    /// swarms seen within the last memory_ticks that are out of sight now.
+    /// Memories are dropped early once their estimated position is back in vision.
+    pub remembered: Vec<RememberedContact>,
+    /// beacons seen before that are out of sight now, nearest first
+    pub remembered_beacons: Vec<Beacon>,
+    /// when the swarm last saw each part of the arena
+    pub explored: Arc<ExplorationMap>,
+}
+
+impl Observation {
+    /// How much a memory can still be trusted, from 1.0 when just seen to 0.0 when forgotten
+    pub fn confidence(&self, contact: &RememberedContact) -> f32 {
+        let age = self.tick.saturating_sub(contact.last_seen) as f32;
+        (1.0 - age / self.own.memory_ticks.max(1) as f32).max(0.0)
+    }
+
+    /// Where a remembered swarm should be by now, assuming it kept its last velocity
+    pub fn estimated_pos(&self, contact: &RememberedContact) -> Vec2 {
+        let pos = estimated_pos(contact, self.tick, &self.bounds);
+        self.bounds.nearest_image(self.own.center, pos)
+    }
+
+    /// Beacons the swarm should remember after this tick: everything in sight
+    /// plus the remembered beacons that weren't seen gone
+    pub fn beacon_memory(&self) -> Vec<Beacon> {
+        self.beacons
+            .iter()
+            .chain(&self.remembered_beacons)
+            .cloned()
+            .collect()
+    }
+
+    /// Contacts the swarm should remember after this tick: everything in sight
+    /// plus the memories that are still valid
+    pub fn memory(&self) -> Vec<RememberedContact> {
+        self.contacts
+            .iter()
+            .map(|contact| RememberedContact::from_contact(contact, self.tick))
+            .chain(self.remembered.iter().cloned())
+            .collect()
+    }
+}
+
+pub(crate) fn estimated_pos(contact: &RememberedContact, tick: u64, bounds: &Bounds) -> Vec2 {
+    let age = tick.saturating_sub(contact.last_seen) as f32;
+    bounds.clamp(contact.pos + contact.velocity * age)
+}
+
+/// Deterministic noise in -1.0..1.0, a pure function of its inputs (SplitMix64),
+/// so observations don't consume random numbers and can be built in any order
+pub(crate) fn noise(seed: u64, tick: u64, observer: u64, observed: u64, axis: u64) -> f32 {
+    let mut z = seed
+        ^ tick.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ observer.wrapping_mul(0xbf58_476d_1ce4_e5b9)
+        ^ observed.wrapping_mul(0x94d0_49bb_1331_11eb)
+        ^ axis.wrapping_mul(0xd6e8_feb8_6659_fd93);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    // top 24 bits give an exactly representable f32 in 0.0..1.0
+    (z >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0
+}
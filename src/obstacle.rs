@@ -0,0 +1,154 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// How far ships are pushed past an obstacle surface, so they end up clearly outside
+const SURFACE_MARGIN: f32 = 0.01;
+
+/// Static arena geometry that ships can't fly through
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Obstacle {
+    /// solid disc
+    Circle { center: Vec2, radius: f32 },
+    /// solid polygon like an asteroid, corners in order
+    Polygon { points: Vec<Vec2> },
+    /// outer walls, everything outside the polygon is solid. The polygon
+    /// doesn't have to be convex, corners in order.
+    Walls { points: Vec<Vec2> },
+}
+
+impl Obstacle {
+    /// Whether `pos` lies in the solid part of the obstacle
+    pub fn contains(&self, pos: Vec2) -> bool {
+        match self {
+            Obstacle::Circle { center, radius } => pos.distance_squared(*center) < radius * radius,
+            Obstacle::Polygon { points } => polygon_contains(points, pos),
+            Obstacle::Walls { points } => !polygon_contains(points, pos),
+        }
+    }
+
+    /// Distance along the normalized direction `dir` until the ray from `pos`
+    /// hits the obstacle surface, None if it never does
+    pub fn raycast(&self, pos: Vec2, dir: Vec2) -> Option<f32> {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                let to_pos = pos - *center;
+                let b = to_pos.dot(dir);
+                let c = to_pos.length_squared() - radius * radius;
+                let disc = b * b - c;
+                if disc < 0.0 {
+                    return None;
+                }
+                let root = disc.sqrt();
+                [-b - root, -b + root].into_iter().find(|t| *t > 0.0)
+            }
+            Obstacle::Polygon { points } | Obstacle::Walls { points } => edges(points)
+                .filter_map(|(a, b)| segment_hit(pos, dir, a, b).filter(|t| *t > 0.0))
+                .min_by(f32::total_cmp),
+        }
+    }
+
+    /// Whether the straight line between `from` and `to` touches the solid part
+    pub fn blocks(&self, from: Vec2, to: Vec2) -> bool {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                closest_on_segment(*center, from, to).distance_squared(*center) < radius * radius
+            }
+            Obstacle::Polygon { points } | Obstacle::Walls { points } => {
+                self.contains(from)
+                    || edges(points).any(|(a, b)| {
+                        segment_hit(from, to - from, a, b).is_some_and(|t| (0.0..=1.0).contains(&t))
+                    })
+            }
+        }
+    }
+
+    /// Closest point on the obstacle surface
+    pub fn closest_surface_point(&self, pos: Vec2) -> Vec2 {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                let dir = (pos - *center).try_normalize().unwrap_or(Vec2::X);
+                *center + dir * *radius
+            }
+            Obstacle::Polygon { points } | Obstacle::Walls { points } => edges(points)
+                .map(|(a, b)| closest_on_segment(pos, a, b))
+                .min_by(|a, b| a.distance_squared(pos).total_cmp(&b.distance_squared(pos)))
+                .unwrap_or(pos),
+        }
+    }
+}
+
+/// Whether `pos` is outside of all obstacles
+pub fn is_free(obstacles: &[Obstacle], pos: Vec2) -> bool {
+    !obstacles.iter().any(|obstacle| obstacle.contains(pos))
+}
+
+/// Whether no obstacle blocks the straight line between `from` and `to`
+pub fn line_of_sight(obstacles: &[Obstacle], from: Vec2, to: Vec2) -> bool {
+    !obstacles.iter().any(|obstacle| obstacle.blocks(from, to))
+}
+
+/// Moves a position that ended up inside obstacles back onto their surface.
+/// Returns the corrected position and velocity, the part of the velocity that
+/// points into the surface is removed, so ships slide along obstacles.
+pub fn collide(obstacles: &[Obstacle], mut pos: Vec2, mut vel: Vec2) -> (Vec2, Vec2) {
+    for obstacle in obstacles {
+        if !obstacle.contains(pos) {
+            continue;
+        }
+        let surface = obstacle.closest_surface_point(pos);
+        // points out of the solid part
+        let Some(normal) = (surface - pos).try_normalize() else {
+            continue;
+        };
+        pos = surface + normal * SURFACE_MARGIN;
+        vel -= normal * vel.dot(normal).min(0.0);
+    }
+    (pos, vel)
+}
+
+/// Polygon edges as (start, end), including the closing edge
+fn edges(points: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+/// Even-odd rule, works for convex and concave polygons
+fn polygon_contains(points: &[Vec2], pos: Vec2) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(points) {
+        if (a.y > pos.y) != (b.y > pos.y) {
+            let x = a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if pos.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Where the line `pos + t * dir` crosses the segment a-b, as t. None for
+/// parallel lines or when the segment is missed.
+fn segment_hit(pos: Vec2, dir: Vec2, a: Vec2, b: Vec2) -> Option<f32> {
+    let edge = b - a;
+    let denom = dir.perp_dot(edge);
+    if denom.abs() < f32::EPSILON {
+        return None;
+    }
+    let to_a = a - pos;
+    let t = to_a.perp_dot(edge) / denom;
+    let s = to_a.perp_dot(dir) / denom;
+    (0.0..=1.0).contains(&s).then_some(t)
+}
+
+fn closest_on_segment(pos: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let edge = b - a;
+    let len_sq = edge.length_squared();
+    if len_sq == 0.0 {
+        return a;
+    }
+    let s = ((pos - a).dot(edge) / len_sq).clamp(0.0, 1.0);
+    a + edge * s
+}
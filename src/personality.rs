@@ -0,0 +1,134 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Uniform distribution over `min..=max`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ParamRange {
+    pub const fn new(min: f32, max: f32) -> ParamRange {
+        ParamRange { min, max }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> f32 {
+        if self.min == self.max {
+            return self.min;
+        }
+        rng.random_range(self.min..=self.max)
+    }
+}
+
+/// Decision making parameters of a single swarm, sampled once at spawn.
+/// Size ratios compare the enemy's ship count to our own.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SwarmPersonality {
+    /// chase enemies with at most this size ratio
+    pub engage_ratio: f32,
+    /// flee from enemies with at least this size ratio, enemies between
+    /// engage_ratio and flee_ratio are watched from hover_distance
+    pub flee_ratio: f32,
+    /// distance kept to enemies that are neither prey nor threat
+    pub hover_distance: f32,
+    /// 0.0 = always regenerate at beacons first, 1.0 = always hunt first
+    pub aggression: f32,
+    /// 0.0 = flee from every visible threat, 0.5 = ignore threats in the outer half of vision range
+    pub risk_tolerance: f32,
+    /// how far ahead the flee target is placed
+    pub flee_distance: f32,
+    /// angular spread of enemy repulsion in radians
+    pub enemy_sigma: f32,
+    /// angular spread of wall repulsion in radians
+    pub wall_sigma: f32,
+    /// angular spread of the turn penalty in radians
+    pub velocity_sigma: f32,
+    /// strength of the penalty for turning away from the current heading
+    pub velocity_penalty: f32,
+}
+
+/// Distributions the personality of each new swarm is drawn from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PersonalityConfig {
+    pub engage_ratio: ParamRange,
+    pub flee_ratio: ParamRange,
+    pub hover_distance: ParamRange,
+    pub aggression: ParamRange,
+    pub risk_tolerance: ParamRange,
+    pub flee_distance: ParamRange,
+    pub enemy_sigma: ParamRange,
+    pub wall_sigma: ParamRange,
+    pub velocity_sigma: ParamRange,
+    pub velocity_penalty: ParamRange,
+}
+
+impl Default for PersonalityConfig {
+    fn default() -> Self {
+        PersonalityConfig {
+            engage_ratio: ParamRange::new(0.5, 0.8),
+            flee_ratio: ParamRange::new(0.9, 1.3),
+            hover_distance: ParamRange::new(300.0, 450.0),
+            aggression: ParamRange::new(0.0, 1.0),
+            risk_tolerance: ParamRange::new(0.0, 0.5),
+            flee_distance: ParamRange::new(300.0, 500.0),
+            enemy_sigma: ParamRange::new(0.6, 1.0),
+            wall_sigma: ParamRange::new(0.4, 0.6),
+            velocity_sigma: ParamRange::new(0.8, 1.2),
+            velocity_penalty: ParamRange::new(0.2, 0.4),
+        }
+    }
+}
+
+impl PersonalityConfig {
+    /// Draw a personality, parameters are sampled in declaration order
+    pub fn sample(&self, rng: &mut impl Rng) -> SwarmPersonality {
+        SwarmPersonality {
+            engage_ratio: self.engage_ratio.sample(rng),
+            flee_ratio: self.flee_ratio.sample(rng),
+            hover_distance: self.hover_distance.sample(rng),
+            aggression: self.aggression.sample(rng),
+            risk_tolerance: self.risk_tolerance.sample(rng),
+            flee_distance: self.flee_distance.sample(rng),
+            enemy_sigma: self.enemy_sigma.sample(rng),
+            wall_sigma: self.wall_sigma.sample(rng),
+            velocity_sigma: self.velocity_sigma.sample(rng),
+            velocity_penalty: self.velocity_penalty.sample(rng),
+        }
+    }
+
+    /// Personality in the middle of all ranges, without randomness
+    pub fn midpoint(&self) -> SwarmPersonality {
+        let mid = |range: ParamRange| (range.min + range.max) / 2.0;
+        SwarmPersonality {
+            engage_ratio: mid(self.engage_ratio),
+            flee_ratio: mid(self.flee_ratio),
+            hover_distance: mid(self.hover_distance),
+            aggression: mid(self.aggression),
+            risk_tolerance: mid(self.risk_tolerance),
+            flee_distance: mid(self.flee_distance),
+            enemy_sigma: mid(self.enemy_sigma),
+            wall_sigma: mid(self.wall_sigma),
+            velocity_sigma: mid(self.velocity_sigma),
+            velocity_penalty: mid(self.velocity_penalty),
+        }
+    }
+
+    /// All ranges with their names, for validation
+    pub fn ranges(&self) -> [(&'static str, ParamRange); 10] {
+        [
+            ("engage_ratio", self.engage_ratio),
+            ("flee_ratio", self.flee_ratio),
+            ("hover_distance", self.hover_distance),
+            ("aggression", self.aggression),
+            ("risk_tolerance", self.risk_tolerance),
+            ("flee_distance", self.flee_distance),
+            ("enemy_sigma", self.enemy_sigma),
+            ("wall_sigma", self.wall_sigma),
+            ("velocity_sigma", self.velocity_sigma),
+            ("velocity_penalty", self.velocity_penalty),
+        ]
+    }
+}
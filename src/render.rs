@@ -1,7 +1,10 @@
 use macroquad::prelude::*;
 
+use crate::beacon::{Beacon, BeaconConfig};
+use crate::obstacle::Obstacle;
 use crate::ship::Ship;
 use crate::swarm::Swarm;
+use crate::zone::Zone;
 
 pub fn draw_ship(ship: &Ship, color: Color) {
     let pos = ship.pos;
@@ -88,6 +91,65 @@ pub fn draw_swarm(swarm: &Swarm, color: Color) {
     );
 }
 
+/// Draw a beacon, the capture progress is shown as a filling ring in the capturing swarm's color
+pub fn draw_beacon(beacon: &Beacon, config: &BeaconConfig, capture_color: Option<Color>) {
+    let pos = beacon.pos;
+    draw_circle(pos.x, pos.y, 6.0, GOLD);
+    draw_circle_lines(
+        pos.x,
+        pos.y,
+        config.capture_radius,
+        1.0,
+        GOLD.with_alpha(0.5),
+    );
+
+    if let (Some(capture), Some(color)) = (beacon.capture, capture_color) {
+        let progress = capture.progress as f32 / config.capture_ticks as f32;
+        draw_arc(
+            pos.x,
+            pos.y,
+            32,
+            config.capture_radius,
+            0.0,
+            3.0,
+            progress * 360.0,
+            color,
+        );
+    }
+}
+
+/// Draw an obstacle, polygons are filled as a triangle fan from their first corner,
+/// which is exact for convex ones. Outer walls are only outlined.
+pub fn draw_obstacle(obstacle: &Obstacle, color: Color) {
+    match obstacle {
+        Obstacle::Circle { center, radius } => draw_circle(center.x, center.y, *radius, color),
+        Obstacle::Polygon { points } => {
+            for pair in points[1..].windows(2) {
+                draw_triangle(points[0], pair[0], pair[1], color);
+            }
+        }
+        Obstacle::Walls { points } => {
+            for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+                draw_line(a.x, a.y, b.x, b.y, 4.0, color);
+            }
+        }
+    }
+}
+
+/// Outline of the playable zone
+pub fn draw_zone(zone: &Zone, color: Color) {
+    match *zone {
+        Zone::Circle { center, radius } => {
+            draw_circle_lines(center.x, center.y, radius, 3.0, color)
+        }
+        Zone::Rectangle { center, half_size } => {
+            let min = center - half_size;
+            let size = half_size * 2.0;
+            draw_rectangle_lines(min.x, min.y, size.x, size.y, 3.0, color);
+        }
+    }
+}
+
 pub fn draw_background_cover(texture: &Texture2D, aspect_ratio: f32) {
     let screen_aspect = screen_width() / screen_height();
 
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::brain::BrainRegistry;
+use crate::simulation::Simulation;
+use crate::snapshot::{SNAPSHOT_VERSION, SimulationSnapshot, SnapshotError, check_version};
+
+/// Number of ticks between keyframes cached by the replay player
+const KEYFRAME_INTERVAL: u64 = 300;
+
+/// A recorded match. The simulation is deterministic, so the initial state
+/// and the number of recorded ticks are enough to reproduce every tick.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub initial: SimulationSnapshot,
+    /// number of ticks recorded after the initial state
+    pub ticks: u64,
+    /// hash of the encoded state after the last tick, to detect diverging playback
+    pub final_state_hash: u64,
+}
+
+/// FNV-1a hash of the complete encoded simulation state, stable across builds
+pub fn state_hash(sim: &Simulation) -> Result<u64, SnapshotError> {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = sim
+        .snapshot()
+        .to_bytes()?
+        .iter()
+        .fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+        });
+    Ok(hash)
+}
+
+impl Replay {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnapshotError> {
+        postcard::to_stdvec(self).map_err(SnapshotError::Encoding)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, SnapshotError> {
+        check_version(bytes)?;
+        postcard::from_bytes(bytes).map_err(SnapshotError::Encoding)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        std::fs::write(path, self.to_bytes()?).map_err(SnapshotError::Io)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, SnapshotError> {
+        let bytes = std::fs::read(path).map_err(SnapshotError::Io)?;
+        Replay::from_bytes(&bytes)
+    }
+
+    /// Tick of the initial state
+    pub fn start_tick(&self) -> u64 {
+        self.initial.tick
+    }
+
+    /// Tick of the last recorded state
+    pub fn end_tick(&self) -> u64 {
+        self.initial.tick + self.ticks
+    }
+}
+
+/// Records a match by remembering the state it started from
+pub struct ReplayRecorder {
+    initial: SimulationSnapshot,
+}
+
+impl ReplayRecorder {
+    /// Start recording from the current state of `sim`
+    pub fn start(sim: &Simulation) -> ReplayRecorder {
+        ReplayRecorder {
+            initial: sim.snapshot(),
+        }
+    }
+
+    /// Create a replay of everything from the start of the recording up to
+    /// the current state of `sim`. Recording continues afterwards.
+    pub fn replay(&self, sim: &Simulation) -> Result<Replay, SnapshotError> {
+        Ok(Replay {
+            version: SNAPSHOT_VERSION,
+            initial: self.initial.clone(),
+            ticks: sim.tick() - self.initial.tick,
+            final_state_hash: state_hash(sim)?,
+        })
+    }
+}
+
+/// Plays back a replay with support for seeking in both directions.
+/// Keyframes are cached while playing, so seeking backwards only has to
+/// re-simulate from the nearest earlier keyframe.
+pub struct ReplayPlayer {
+    replay: Replay,
+    sim: Simulation,
+    keyframes: BTreeMap<u64, SimulationSnapshot>,
+    brains: BrainRegistry,
+}
+
+impl ReplayPlayer {
+    /// Player for a replay that only uses the default brain
+    pub fn new(replay: Replay) -> Result<ReplayPlayer, SnapshotError> {
+        ReplayPlayer::with_brains(replay, BrainRegistry::default())
+    }
+
+    /// Player that restores the brains of the recorded swarms from `brains`
+    pub fn with_brains(
+        replay: Replay,
+        brains: BrainRegistry,
+    ) -> Result<ReplayPlayer, SnapshotError> {
+        let sim = Simulation::from_snapshot_with_brains(replay.initial.clone(), &brains)?;
+        let mut keyframes = BTreeMap::new();
+        keyframes.insert(replay.start_tick(), replay.initial.clone());
+        Ok(ReplayPlayer {
+            replay,
+            sim,
+            keyframes,
+            brains,
+        })
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Current state of the playback
+    pub fn sim(&self) -> &Simulation {
+        &self.sim
+    }
+
+    /// Mutable access, e.g. to drain events during playback
+    pub fn sim_mut(&mut self) -> &mut Simulation {
+        &mut self.sim
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.sim.tick()
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.tick() >= self.replay.end_tick()
+    }
+
+    /// Advance by one tick, returns false at the end of the replay
+    pub fn step(&mut self) -> bool {
+        if self.at_end() {
+            return false;
+        }
+        self.sim.step();
+        let tick = self.sim.tick();
+        if (tick - self.replay.start_tick()).is_multiple_of(KEYFRAME_INTERVAL) {
+            self.keyframes
+                .entry(tick)
+                .or_insert_with(|| self.sim.snapshot());
+        }
+        true
+    }
+
+    /// Jump to the given tick, clamped to the recorded range. Fails if a
+    /// keyframe uses a brain that is not registered.
+    pub fn seek(&mut self, tick: u64) -> Result<(), SnapshotError> {
+        let tick = tick.clamp(self.replay.start_tick(), self.replay.end_tick());
+
+        // restore the nearest earlier keyframe, unless stepping from here is shorter
+        let (_, keyframe) = self.keyframes.range(..=tick).next_back().unwrap();
+        if tick < self.sim.tick() || keyframe.tick > self.sim.tick() {
+            self.sim = Simulation::from_snapshot_with_brains(keyframe.clone(), &self.brains)?;
+        }
+
+        while self.sim.tick() < tick {
+            self.step();
+        }
+        Ok(())
+    }
+
+    /// Whether playback reproduced the recorded final state, None before the end
+    pub fn verify(&self) -> Option<bool> {
+        if !self.at_end() {
+            return None;
+        }
+        let hash = state_hash(&self.sim).ok()?;
+        Some(hash == self.replay.final_state_hash)
+    }
+}
@@ -53,8 +53,14 @@ impl RepulsionMap {
         }
     }
 
+    /// Add an attractor at the given angle, the opposite of a repulsor
+    pub fn add_attractor(&mut self, angle: f32, strength: f32, sigma: f32) {
+        self.add_repulsor(angle, -strength, sigma);
+    }
+
     /// Add wall repulsion by raycasting from the given position in each direction.
-    /// Walls closer than detect_range will add repulsion proportional to proximity.
+    /// Walls (none on a torus), obstacles and the zone edge closer than
+    /// detect_range will add repulsion proportional to proximity.
     /// The total wall repulsion is normalized to prevent walls from overwhelming
     /// enemy repulsors.
     pub fn add_wall_repulsion(
@@ -133,9 +139,18 @@ impl Default for RepulsionMap {
 }
 
 /// Raycast from a position in a given direction to find the distance to the boundary.
-/// Returns the distance to the closest wall intersection.
+/// Returns the distance to the closest wall, obstacle or zone edge intersection.
 fn raycast_to_bounds(pos: Vec2, dir: Vec2, bounds: &Bounds) -> f32 {
-    let mut min_dist = f32::MAX;
+    let mut min_dist = bounds
+        .obstacles
+        .iter()
+        .filter_map(|obstacle| obstacle.raycast(pos, dir))
+        .chain(bounds.zone.and_then(|zone| zone.raycast(pos, dir)))
+        .fold(f32::MAX, f32::min);
+    // a torus has no walls
+    if bounds.wrap_around {
+        return min_dist;
+    }
 
     // Check each wall
     // Left wall (x = min.x)
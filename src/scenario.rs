@@ -0,0 +1,431 @@
+use std::fmt;
+use std::path::Path;
+
+use glam::Vec2;
+use serde::Deserialize;
+
+use crate::boundary::Boundary;
+use crate::obstacle::{self, Obstacle};
+use crate::ship::ShipConfig;
+use crate::simulation::{Bounds, Simulation, SimulationConfig};
+use crate::spawner::SpawnLocation;
+use crate::swarm::{GrowthCurve, SwarmConfig, TeamId};
+
+/// Everything needed to set up a simulation, loaded from a TOML file.
+/// All sections are optional and fall back to their defaults.
+///
+/// ```toml
+/// [simulation]
+/// seed = 42
+/// init_swarms = 4
+/// respawn_location = { kind = "farthest_from_enemies", margin = 100.0, candidates = 16 }
+/// swarm_size = { kind = "uniform", min = 5, max = 20 }
+///
+/// [simulation.beacons]
+/// spawn_interval = 300
+/// payload = { kind = "fixed", ships = 3 }
+///
+/// [simulation.zone]
+/// shape = "circle"
+/// schedule = [{ tick = 3000, scale = 1.0 }, { tick = 9000, scale = 0.2 }]
+///
+/// [arena]
+/// width = 1500.0
+/// height = 1000.0
+/// boundary = "bounce"
+///
+/// [[arena.obstacles]]
+/// circle = { center = [750.0, 500.0], radius = 120.0 }
+///
+/// [[arena.obstacles]]
+/// polygon = { points = [[300.0, 700.0], [450.0, 650.0], [400.0, 850.0]] }
+///
+/// [ship]
+/// max_speed = 8.0
+///
+/// [swarm]
+/// max_ships = 20
+///
+/// [[swarms]]
+/// pos = [200.0, 300.0]
+/// ships = 12
+/// team = 0
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scenario {
+    pub simulation: SimulationConfig,
+    pub arena: ArenaConfig,
+    pub ship: ShipConfig,
+    pub swarm: SwarmConfig,
+    /// swarms placed at the start, these count towards init_swarms
+    pub swarms: Vec<SwarmSpawn>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArenaConfig {
+    pub width: f32,
+    pub height: f32,
+    /// circles, polygons and outer walls ships can't fly through
+    pub obstacles: Vec<Obstacle>,
+    /// torus arena without walls, ships leaving one edge re-enter on the opposite side
+    pub wrap_around: bool,
+    /// what the walls do to ships that fly into them: clamp (default),
+    /// nothing (open), bounce or damage
+    pub boundary: Boundary,
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        ArenaConfig {
+            width: 1980.0,
+            height: 1980.0,
+            obstacles: Vec::new(),
+            wrap_around: false,
+            boundary: Boundary::default(),
+        }
+    }
+}
+
+/// A swarm placed at a fixed position when the scenario is built
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SwarmSpawn {
+    pub pos: Vec2,
+    pub ships: u32,
+    /// swarms of the same team are allied, without a team a swarm fights everybody
+    #[serde(default)]
+    pub team: Option<TeamId>,
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    /// scenario file could not be read
+    Io(std::io::Error),
+    /// malformed TOML, unknown keys or wrong value types
+    Parse(toml::de::Error),
+    /// well-formed scenario with values out of range
+    Invalid(String),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(err) => write!(f, "failed to read scenario: {err}"),
+            ScenarioError::Parse(err) => write!(f, "failed to parse scenario: {err}"),
+            ScenarioError::Invalid(msg) => write!(f, "invalid scenario: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+/// Check that a value is strictly positive (rejects NaN)
+fn positive(name: &str, value: f32) -> Result<(), ScenarioError> {
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(ScenarioError::Invalid(format!(
+            "{name} must be > 0, got {value}"
+        )))
+    }
+}
+
+/// Check that a value lies within `min..=max` (rejects NaN)
+fn in_range(name: &str, value: f32, min: f32, max: f32) -> Result<(), ScenarioError> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(ScenarioError::Invalid(format!(
+            "{name} must be within {min}..={max}, got {value}"
+        )))
+    }
+}
+
+/// Check that a margin to the arena walls leaves room to place something,
+/// `0..min(width, height) / 2` (rejects NaN)
+fn wall_margin(name: &str, value: f32, arena: &ArenaConfig) -> Result<(), ScenarioError> {
+    let max = arena.width.min(arena.height) / 2.0;
+    if (0.0..max).contains(&value) {
+        Ok(())
+    } else {
+        Err(ScenarioError::Invalid(format!(
+            "{name} must be within 0..{max}, got {value}"
+        )))
+    }
+}
+
+fn nonzero(name: &str, value: u32) -> Result<(), ScenarioError> {
+    if value > 0 {
+        Ok(())
+    } else {
+        Err(ScenarioError::Invalid(format!("{name} must be > 0, got 0")))
+    }
+}
+
+/// Check that a penalty curve ends within `min..=max` and is never zero
+fn growth_curve(name: &str, curve: &GrowthCurve, min: f32, max: f32) -> Result<(), ScenarioError> {
+    in_range(&format!("{name}.at_max"), curve.at_max, min, max)?;
+    positive(&format!("{name}.at_max"), curve.at_max)?;
+    positive(&format!("{name}.exponent"), curve.exponent)
+}
+
+/// Check that a spawn location leaves room inside the arena
+fn spawn_location(
+    name: &str,
+    location: &SpawnLocation,
+    arena: &ArenaConfig,
+) -> Result<(), ScenarioError> {
+    match *location {
+        SpawnLocation::Edge => Ok(()),
+        SpawnLocation::Interior { margin } => wall_margin(&format!("{name}.margin"), margin, arena),
+        SpawnLocation::FarthestFromEnemies { margin, candidates } => {
+            wall_margin(&format!("{name}.margin"), margin, arena)?;
+            nonzero(&format!("{name}.candidates"), candidates)
+        }
+    }
+}
+
+impl Scenario {
+    /// Parse and validate a scenario from a TOML string
+    pub fn from_toml(source: &str) -> Result<Scenario, ScenarioError> {
+        let scenario: Scenario = toml::from_str(source).map_err(ScenarioError::Parse)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Read, parse and validate a scenario file
+    pub fn load(path: impl AsRef<Path>) -> Result<Scenario, ScenarioError> {
+        let source = std::fs::read_to_string(path).map_err(ScenarioError::Io)?;
+        Scenario::from_toml(&source)
+    }
+
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        let sim = &self.simulation;
+        nonzero("simulation.max_swarms", sim.max_swarms)?;
+        if sim.init_swarms > sim.max_swarms {
+            return Err(ScenarioError::Invalid(format!(
+                "simulation.init_swarms ({}) must not exceed simulation.max_swarms ({})",
+                sim.init_swarms, sim.max_swarms
+            )));
+        }
+
+        if self.swarms.len() > sim.max_swarms as usize {
+            return Err(ScenarioError::Invalid(format!(
+                "{} swarms listed, but simulation.max_swarms is {}",
+                self.swarms.len(),
+                sim.max_swarms
+            )));
+        }
+
+        positive("arena.width", self.arena.width)?;
+        positive("arena.height", self.arena.height)?;
+        for (idx, obstacle) in self.arena.obstacles.iter().enumerate() {
+            let name = format!("arena.obstacles[{idx}]");
+            match obstacle {
+                Obstacle::Circle { radius, .. } => positive(&format!("{name}.radius"), *radius)?,
+                Obstacle::Walls { .. } if self.arena.wrap_around => {
+                    return Err(ScenarioError::Invalid(format!(
+                        "{name}: walls don't work with arena.wrap_around"
+                    )));
+                }
+                Obstacle::Polygon { points } | Obstacle::Walls { points } => {
+                    if points.len() < 3 {
+                        return Err(ScenarioError::Invalid(format!(
+                            "{name} needs at least 3 points, got {}",
+                            points.len()
+                        )));
+                    }
+                }
+            }
+        }
+        if let Boundary::Damage { interval, .. } = self.arena.boundary {
+            nonzero("arena.boundary.damage.interval", interval)?;
+        }
+        spawn_location("simulation.init_location", &sim.init_location, &self.arena)?;
+        spawn_location(
+            "simulation.respawn_location",
+            &sim.respawn_location,
+            &self.arena,
+        )?;
+
+        let ship = &self.ship;
+        positive("ship.max_speed", ship.max_speed)?;
+        positive("ship.max_accel", ship.max_accel)?;
+        positive("ship.max_decel", ship.max_decel)?;
+        in_range("ship.aim_range", ship.aim_range, 0.0, f32::MAX)?;
+        in_range(
+            "ship.lock_time_factor",
+            ship.lock_time_factor,
+            1.0,
+            f32::MAX,
+        )?;
+        nonzero("ship.health", ship.health)?;
+
+        let swarm = &self.swarm;
+        nonzero("swarm.max_ships", swarm.max_ships)?;
+        in_range("swarm.scale", swarm.scale, 0.0, f32::MAX)?;
+        positive("swarm.vision_range", swarm.vision_range)?;
+        in_range(
+            "swarm.observation_noise",
+            swarm.observation_noise,
+            0.0,
+            f32::MAX,
+        )?;
+        in_range("swarm.kite_range", swarm.kite_range, 0.0, f32::MAX)?;
+        positive("swarm.max_turn_rate", swarm.max_turn_rate)?;
+        growth_curve("swarm.accel_penalty", &swarm.accel_penalty, 0.0, 1.0)?;
+        growth_curve("swarm.speed_penalty", &swarm.speed_penalty, 0.0, 1.0)?;
+        growth_curve("swarm.turn_penalty", &swarm.turn_penalty, 0.0, 1.0)?;
+        growth_curve("swarm.lock_penalty", &swarm.lock_penalty, 1.0, f32::MAX)?;
+
+        let personality = &swarm.personality;
+        for (name, range) in personality.ranges() {
+            let name = format!("swarm.personality.{name}");
+            in_range(&format!("{name}.min"), range.min, 0.0, f32::MAX)?;
+            in_range(&format!("{name}.max"), range.max, range.min, f32::MAX)?;
+        }
+        in_range(
+            "swarm.personality.aggression.max",
+            personality.aggression.max,
+            0.0,
+            1.0,
+        )?;
+        in_range(
+            "swarm.personality.risk_tolerance.max",
+            personality.risk_tolerance.max,
+            0.0,
+            1.0,
+        )?;
+        in_range(
+            "swarm.personality.engage_ratio.max",
+            personality.engage_ratio.max,
+            0.0,
+            personality.flee_ratio.min,
+        )?;
+        positive(
+            "swarm.personality.enemy_sigma.min",
+            personality.enemy_sigma.min,
+        )?;
+        positive(
+            "swarm.personality.wall_sigma.min",
+            personality.wall_sigma.min,
+        )?;
+        positive(
+            "swarm.personality.velocity_sigma.min",
+            personality.velocity_sigma.min,
+        )?;
+
+        let beacons = &sim.beacons;
+        wall_margin("simulation.beacons.margin", beacons.margin, &self.arena)?;
+        in_range(
+            "simulation.beacons.capture_radius",
+            beacons.capture_radius,
+            0.0,
+            f32::MAX,
+        )?;
+        nonzero("simulation.beacons.capture_ticks", beacons.capture_ticks)?;
+        in_range(
+            "simulation.beacons.capture_lock_factor",
+            beacons.capture_lock_factor,
+            1.0,
+            f32::MAX,
+        )?;
+        let (min_payload, max_payload) = beacons.payload.range();
+        nonzero("simulation.beacons.payload", min_payload)?;
+        if min_payload > max_payload {
+            return Err(ScenarioError::Invalid(format!(
+                "simulation.beacons.payload.min ({min_payload}) must not exceed simulation.beacons.payload.max ({max_payload})"
+            )));
+        }
+
+        let zone = &sim.zone;
+        if zone.shape.is_some() {
+            if self.arena.wrap_around {
+                return Err(ScenarioError::Invalid(
+                    "simulation.zone doesn't work with arena.wrap_around".to_string(),
+                ));
+            }
+            wall_margin("simulation.zone.margin", zone.margin, &self.arena)?;
+            nonzero("simulation.zone.damage_interval", zone.damage_interval)?;
+            for (idx, step) in zone.schedule.iter().enumerate() {
+                let name = format!("simulation.zone.schedule[{idx}]");
+                in_range(&format!("{name}.scale"), step.scale, 0.0, 1.0)?;
+                if idx > 0 && step.tick < zone.schedule[idx - 1].tick {
+                    return Err(ScenarioError::Invalid(format!(
+                        "{name}.tick ({}) must not be before the previous step",
+                        step.tick
+                    )));
+                }
+            }
+        }
+
+        let (min_size, max_size) = sim.swarm_size.range();
+        nonzero("simulation.swarm_size", min_size)?;
+        if min_size > max_size {
+            return Err(ScenarioError::Invalid(format!(
+                "simulation.swarm_size.min ({min_size}) must not exceed simulation.swarm_size.max ({max_size})"
+            )));
+        }
+        if max_size > swarm.max_ships {
+            return Err(ScenarioError::Invalid(format!(
+                "simulation.swarm_size ({max_size}) must not exceed swarm.max_ships ({})",
+                swarm.max_ships
+            )));
+        }
+
+        for (idx, spawn) in self.swarms.iter().enumerate() {
+            nonzero(&format!("swarms[{idx}].ships"), spawn.ships)?;
+            if spawn.ships > swarm.max_ships {
+                return Err(ScenarioError::Invalid(format!(
+                    "swarms[{idx}].ships ({}) must not exceed swarm.max_ships ({})",
+                    spawn.ships, swarm.max_ships
+                )));
+            }
+            in_range(
+                &format!("swarms[{idx}].pos.x"),
+                spawn.pos.x,
+                0.0,
+                self.arena.width,
+            )?;
+            in_range(
+                &format!("swarms[{idx}].pos.y"),
+                spawn.pos.y,
+                0.0,
+                self.arena.height,
+            )?;
+            if !obstacle::is_free(&self.arena.obstacles, spawn.pos) {
+                return Err(ScenarioError::Invalid(format!(
+                    "swarms[{idx}].pos ({}, {}) is inside an obstacle",
+                    spawn.pos.x, spawn.pos.y
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(self.arena.width, self.arena.height)
+            .with_obstacles(self.arena.obstacles.clone())
+            .with_wrap_around(self.arena.wrap_around)
+            .with_boundary(self.arena.boundary)
+    }
+
+    /// Create the simulation described by this scenario. The listed swarms are
+    /// placed first, then random swarms are added until init_swarms is reached.
+    pub fn build(&self) -> Simulation {
+        let mut sim = Simulation::with_configs(
+            self.simulation.clone(),
+            self.bounds(),
+            self.ship.clone(),
+            self.swarm.clone(),
+        );
+        for spawn in &self.swarms {
+            sim.spawn_team_swarm(spawn.pos, spawn.ships, spawn.team);
+        }
+        sim.spawn_initial_swarms();
+        sim
+    }
+}
@@ -1,22 +1,33 @@
-use std::sync::atomic::{AtomicU64, Ordering};
-
 use glam::Vec2;
-use std::rc::Rc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-const EPSILON: f32 = 0.001;
+use crate::obstacle::{self, Obstacle};
+use crate::simulation::Bounds;
+use crate::snapshot::ShipSnapshot;
 
-static NEXT_SHIP_ID: AtomicU64 = AtomicU64::new(0);
+const EPSILON: f32 = 0.001;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ShipId(pub u64);
 
-impl ShipId {
-    pub fn next() -> Self {
-        ShipId(NEXT_SHIP_ID.fetch_add(1, Ordering::Relaxed))
+/// Hands out unique ship IDs. Every simulation owns its own allocator,
+/// so ID assignment is reproducible between runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShipIdAllocator {
+    next: u64,
+}
+
+impl ShipIdAllocator {
+    pub fn next_id(&mut self) -> ShipId {
+        let id = ShipId(self.next);
+        self.next += 1;
+        id
     }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ShipConfig {
     /// maximum ship velocity magnitude
     pub max_speed: f32,
@@ -32,6 +43,8 @@ pub struct ShipConfig {
     pub lock_time_factor: f32,
     /// initial ship health points (1 hit = 1 damage)
     pub health: u32,
+    /// obstacles between a ship and its target prevent and break locks
+    pub line_of_sight: bool,
 }
 
 impl Default for ShipConfig {
@@ -44,18 +57,20 @@ impl Default for ShipConfig {
             fire_delay: 60,
             lock_time_factor: 2.0,
             health: 3,
+            line_of_sight: true,
         }
     }
 }
 
 /// A single unit. Controlled by a swarm, but works independent.
+#[derive(Clone)]
 pub struct Ship {
     pub id: ShipId,
     pub pos: Vec2,
     pub vel: Vec2,
     pub target_pos: Vec2,
     pub health: u32,
-    pub config: Rc<ShipConfig>,
+    pub config: Arc<ShipConfig>,
 
     /// current lock-on target
     pub lock_target: Option<ShipId>,
@@ -70,9 +85,9 @@ pub struct Ship {
 }
 
 impl Ship {
-    pub fn spawn(pos: Vec2, config: Rc<ShipConfig>) -> Ship {
+    pub fn spawn(id: ShipId, pos: Vec2, config: Arc<ShipConfig>) -> Ship {
         Ship {
-            id: ShipId::next(),
+            id,
             pos,
             vel: Vec2::ZERO,
             target_pos: pos,
@@ -86,6 +101,37 @@ impl Ship {
         }
     }
 
+    pub fn snapshot(&self) -> ShipSnapshot {
+        ShipSnapshot {
+            id: self.id,
+            pos: self.pos,
+            vel: self.vel,
+            target_pos: self.target_pos,
+            health: self.health,
+            lock_target: self.lock_target,
+            lock_progress: self.lock_progress,
+            fired_at: self.fired_at,
+            lock_target_pos: self.lock_target_pos,
+            lock_time: self.lock_time,
+        }
+    }
+
+    pub fn from_snapshot(snapshot: &ShipSnapshot, config: Arc<ShipConfig>) -> Ship {
+        Ship {
+            id: snapshot.id,
+            pos: snapshot.pos,
+            vel: snapshot.vel,
+            target_pos: snapshot.target_pos,
+            health: snapshot.health,
+            config,
+            lock_target: snapshot.lock_target,
+            lock_progress: snapshot.lock_progress,
+            fired_at: snapshot.fired_at,
+            lock_target_pos: snapshot.lock_target_pos,
+            lock_time: snapshot.lock_time,
+        }
+    }
+
     pub fn speed(&self) -> f32 {
         self.vel.length()
     }
@@ -94,18 +140,46 @@ impl Ship {
         self.target_pos = pos;
     }
 
+    /// Whether `target` is in aim range and, with line_of_sight, not hidden behind obstacles
+    pub fn can_aim_at(&self, target: &Ship, bounds: &Bounds) -> bool {
+        let target_pos = bounds.nearest_image(self.pos, target.pos);
+        self.pos.distance(target_pos) <= self.config.aim_range
+            && (!self.config.line_of_sight
+                || obstacle::line_of_sight(&bounds.obstacles, self.pos, target_pos))
+    }
+
+    /// Pushes the ship out of `obstacles` it flew into, keeping only the
+    /// velocity along their surface
+    pub fn collide(&mut self, obstacles: &[Obstacle]) {
+        (self.pos, self.vel) = obstacle::collide(obstacles, self.pos, self.vel);
+    }
+
+    /// Applies the arena walls to a ship that flew into or past them, see
+    /// `Boundary`. Returns whether the ship touched the walls.
+    pub fn confine(&mut self, bounds: &Bounds) -> bool {
+        if bounds.wrap_around {
+            return false;
+        }
+        let touched;
+        (self.pos, self.vel, touched) = bounds
+            .boundary
+            .apply(bounds.min, bounds.max, self.pos, self.vel);
+        touched
+    }
+
     /// TODO: right now ships just accelerate in the direction of target,
     /// or break by decelerating in the opposite direction of velocity
     /// this is not optimal and could lead to ships not reaching their
     /// destination optimally -> gotta investiage :)
     /// A smarter solution would allow to determine a smart acceleration
     /// vector that leads to the optimal / shortest path to the target
-    pub fn movement(&mut self, accel_factor: f32) {
+    pub fn movement(&mut self, accel_factor: f32, speed_factor: f32) {
         let to_target = self.target_pos - self.pos;
         let dist = to_target.length();
         let speed = self.vel.length();
         let max_accel = self.config.max_accel * accel_factor;
         let max_decel = self.config.max_decel * accel_factor;
+        let max_speed = self.config.max_speed * speed_factor;
 
         // close and slow enough -> full stop
         if dist < EPSILON && speed < EPSILON {
@@ -126,7 +200,7 @@ impl Ship {
 
         // Max safe approach speed
         let v_max = -max_decel + (max_decel * max_decel + 2.0 * max_decel * dist).sqrt();
-        let v_max = v_max.min(self.config.max_speed);
+        let v_max = v_max.min(max_speed);
 
         // Desired velocity: toward target at v_max speed
         let desired_vel = dir_to_target * v_max;
@@ -154,8 +228,8 @@ impl Ship {
         }
 
         // Clamp to max speed
-        if self.vel.length() > self.config.max_speed {
-            self.vel = self.vel.normalize() * self.config.max_speed;
+        if self.vel.length() > max_speed {
+            self.vel = self.vel.normalize() * max_speed;
         }
 
         self.pos += self.vel;
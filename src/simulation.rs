@@ -1,29 +1,114 @@
+use std::collections::{HashMap, HashSet};
+
 use glam::Vec2;
-use std::rc::Rc;
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-use crate::ship::{Ship, ShipConfig, ShipId};
-use crate::swarm::{Swarm, SwarmConfig, SwarmDecision};
+use crate::beacon::{Beacon, BeaconConfig, BeaconId, Capture};
+use crate::boundary::Boundary;
+use crate::brain::{BrainFactory, BrainRegistry, SwarmBrain};
+use crate::event::{DestroyCause, Event, EventCallback, EventKind};
+use crate::exploration::ExplorationMap;
+use crate::observation::{
+    Ally, Contact, Observation, OwnSwarm, ShipSighting, estimated_pos, noise,
+};
+use crate::obstacle::{self, Obstacle};
+use crate::ship::{Ship, ShipConfig, ShipId, ShipIdAllocator};
+use crate::snapshot::{SNAPSHOT_VERSION, SimulationSnapshot, SnapshotError};
+use crate::spatial::{SharedVision, SpatialGrid, SpatialIndex};
+use crate::spawner::{SpawnLocation, SwarmSize};
+use crate::stats::SwarmStats;
+use crate::swarm::{Swarm, SwarmConfig, SwarmDecision, SwarmId, TeamId};
+use crate::zone::{Zone, ZoneConfig};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct SimulationConfig {
     /// maximum number of swarms in the simulation
     pub max_swarms: u32,
 
     // initial number of swarms
     pub init_swarms: u32,
+
+    /// seed for all random choices, same seed and config -> same simulation
+    pub seed: u64,
+
+    /// ticks between respawns while below max_swarms (0 = no respawning)
+    pub respawn_interval: u32,
+
+    /// where the initial swarms are placed
+    pub init_location: SpawnLocation,
+
+    /// where respawned swarms are placed
+    pub respawn_location: SpawnLocation,
+
+    /// number of ships in spawned swarms
+    pub swarm_size: SwarmSize,
+
+    /// random swarms join the team with the fewest swarms alive (0 = free-for-all)
+    pub teams: u32,
+
+    /// spawning and capturing of regeneration beacons
+    pub beacons: BeaconConfig,
+
+    /// playable zone that shrinks over time
+    pub zone: ZoneConfig,
 }
 
 impl Default for SimulationConfig {
     fn default() -> Self {
         SimulationConfig {
-            max_swarms: 10,
-            init_swarms: 2,
+            max_swarms: 15,
+            init_swarms: 15,
+            seed: 0,
+            respawn_interval: 60,
+            init_location: SpawnLocation::Interior { margin: 100.0 },
+            respawn_location: SpawnLocation::Edge,
+            swarm_size: SwarmSize::Uniform { min: 2, max: 29 },
+            teams: 0,
+            beacons: BeaconConfig::default(),
+            zone: ZoneConfig::default(),
         }
     }
 }
 
+/// Random positions inside obstacles are re-rolled this often before giving up
+const MAX_PLACEMENT_TRIES: u32 = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bounds {
     pub min: Vec2,
     pub max: Vec2,
+    /// static geometry inside the rectangle, ships collide with it
+    pub obstacles: Arc<Vec<Obstacle>>,
+    /// torus arena: instead of walls, leaving one edge re-enters on the
+    /// opposite side and distances are measured across the edges
+    pub wrap_around: bool,
+    /// what the walls do to ships that fly into them
+    pub boundary: Boundary,
+    /// playable part of the arena at the current tick, moved by the simulation
+    pub zone: Option<Zone>,
+}
+
+/// Random position on a polygon outline, every point is equally likely
+fn random_perimeter_pos(points: &[Vec2], rng: &mut impl Rng) -> Vec2 {
+    let edges: Vec<(Vec2, Vec2)> = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+        .collect();
+    let perimeter: f32 = edges.iter().map(|(a, b)| a.distance(*b)).sum();
+    let mut along = rng.random_range(0.0..perimeter.max(f32::EPSILON));
+    for (a, b) in &edges {
+        let len = a.distance(*b);
+        if along <= len {
+            return a.lerp(*b, along / len.max(f32::EPSILON));
+        }
+        along -= len;
+    }
+    points[0]
 }
 
 impl Bounds {
@@ -31,23 +116,191 @@ impl Bounds {
         Bounds {
             min: Vec2::ZERO,
             max: Vec2::new(width, height),
+            obstacles: Arc::default(),
+            wrap_around: false,
+            boundary: Boundary::default(),
+            zone: None,
+        }
+    }
+
+    pub fn with_obstacles(mut self, obstacles: Vec<Obstacle>) -> Self {
+        self.obstacles = Arc::new(obstacles);
+        self
+    }
+
+    pub fn with_wrap_around(mut self, wrap_around: bool) -> Self {
+        self.wrap_around = wrap_around;
+        self
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    /// Vector from `from` to `to`, across the edges if that is shorter on a torus
+    pub fn delta(&self, from: Vec2, to: Vec2) -> Vec2 {
+        let delta = to - from;
+        if !self.wrap_around {
+            return delta;
+        }
+        let size = self.size();
+        delta - size * (delta / size).round()
+    }
+
+    pub fn distance(&self, a: Vec2, b: Vec2) -> f32 {
+        self.delta(a, b).length()
+    }
+
+    pub fn distance_squared(&self, a: Vec2, b: Vec2) -> f32 {
+        self.delta(a, b).length_squared()
+    }
+
+    /// The copy of `pos` closest to `origin` on a torus, `pos` itself otherwise.
+    /// May lie outside the bounds.
+    pub fn nearest_image(&self, origin: Vec2, pos: Vec2) -> Vec2 {
+        if !self.wrap_around {
+            return pos;
         }
+        origin + self.delta(origin, pos)
     }
 
+    /// Brings a position that left a torus back in on the opposite side
+    pub fn wrap(&self, pos: Vec2) -> Vec2 {
+        if !self.wrap_around {
+            return pos;
+        }
+        self.min + (pos - self.min).rem_euclid(self.size())
+    }
+
+    /// Positions to run an unwrapped range query from, so that it also finds
+    /// everything within `range` across the edges of a torus
+    pub fn images(&self, pos: Vec2, range: f32) -> Vec<Vec2> {
+        if !self.wrap_around {
+            return vec![pos];
+        }
+        let size = self.size();
+        let mut images = Vec::new();
+        for x in [-1.0, 0.0, 1.0] {
+            for y in [-1.0, 0.0, 1.0] {
+                let image = pos + size * Vec2::new(x, y);
+                let overlaps =
+                    (image - range).cmplt(self.max).all() && (image + range).cmpge(self.min).all();
+                if overlaps {
+                    images.push(image);
+                }
+            }
+        }
+        images
+    }
+
+    /// Whether `pos` lies between the walls, always true on a torus
+    pub fn contains(&self, pos: Vec2) -> bool {
+        self.wrap_around || (pos.cmpge(self.min).all() && pos.cmple(self.max).all())
+    }
+
+    /// Whether `pos` is outside of all obstacles and inside the zone
+    pub fn is_free(&self, pos: Vec2) -> bool {
+        obstacle::is_free(&self.obstacles, pos) && self.zone.is_none_or(|zone| zone.contains(pos))
+    }
+
+    /// Random position inside the bounds, keeping a margin to all walls.
+    /// Positions inside obstacles are avoided, with a zone only positions
+    /// around it are picked.
+    pub fn random_pos<R: Rng>(&self, rng: &mut R, margin: f32) -> Vec2 {
+        let mut min = self.min + Vec2::splat(margin);
+        let mut max = self.max - Vec2::splat(margin);
+        if let Some(zone) = self.zone {
+            let (zone_min, zone_max) = zone.bounding_box();
+            min = min.max(zone_min);
+            max = max.min(zone_max);
+        }
+        // a margin of half the arena or more leaves only the center
+        let axis = |rng: &mut R, min: f32, max: f32| {
+            if min < max {
+                rng.random_range(min..max)
+            } else {
+                (min + max) / 2.0
+            }
+        };
+        self.random_free_pos(rng, |rng| {
+            Vec2::new(axis(rng, min.x, max.x), axis(rng, min.y, max.y))
+        })
+    }
+
+    /// Random position on one of the four walls, or on the outer walls
+    /// polygon if the arena has one. With a zone, on the zone edge instead.
+    pub fn random_edge_pos(&self, rng: &mut impl Rng) -> Vec2 {
+        if let Some(zone) = self.zone {
+            // a circle covers the arena corners at first, its edge may be outside
+            return self.random_free_pos(rng, |rng| self.clamp(zone.random_edge_pos(rng)));
+        }
+        let walls = self.obstacles.iter().find_map(|obstacle| match obstacle {
+            Obstacle::Walls { points } => Some(points),
+            _ => None,
+        });
+        self.random_free_pos(rng, |rng| match walls {
+            Some(points) => random_perimeter_pos(points, rng),
+            None => match rng.random_range(0..4) {
+                0 => Vec2::new(rng.random_range(self.min.x..self.max.x), self.min.y),
+                1 => Vec2::new(rng.random_range(self.min.x..self.max.x), self.max.y),
+                2 => Vec2::new(self.min.x, rng.random_range(self.min.y..self.max.y)),
+                _ => Vec2::new(self.max.x, rng.random_range(self.min.y..self.max.y)),
+            },
+        })
+    }
+
+    /// Keeps picking until a position is outside of all obstacles and inside
+    /// the zone. Falls back to the last pick, moved into the zone, if there
+    /// doesn't seem to be any room.
+    fn random_free_pos<R: Rng>(&self, rng: &mut R, mut pick: impl FnMut(&mut R) -> Vec2) -> Vec2 {
+        let mut pos = pick(rng);
+        for _ in 1..MAX_PLACEMENT_TRIES {
+            if self.is_free(pos) {
+                return pos;
+            }
+            pos = pick(rng);
+        }
+        match self.zone {
+            Some(zone) if !self.is_free(pos) => zone.clamp_with_margin(pos, 0.0),
+            _ => pos,
+        }
+    }
+
+    /// Keeps a position inside the bounds, a torus wraps it instead
     pub fn clamp(&self, pos: Vec2) -> Vec2 {
+        if self.wrap_around {
+            return self.wrap(pos);
+        }
         pos.clamp(self.min, self.max)
     }
 
+    /// Keeps a position away from the walls and the zone edge. A torus has
+    /// no walls, so the position is kept as is.
     pub fn clamp_with_margin(&self, pos: Vec2, margin: f32) -> Vec2 {
-        pos.clamp(
+        if self.wrap_around {
+            return pos;
+        }
+        let pos = pos.clamp(
             self.min + Vec2::splat(margin),
             self.max - Vec2::splat(margin),
-        )
+        );
+        match self.zone {
+            Some(zone) => zone.clamp_with_margin(pos, margin),
+            None => pos,
+        }
     }
 
     /// Returns a vector pointing away from nearby walls, with strength based on proximity.
     /// Handles corners by combining repulsion from multiple walls.
     pub fn wall_avoidance(&self, pos: Vec2, detect_range: f32) -> Vec2 {
+        if self.wrap_around {
+            return Vec2::ZERO;
+        }
         let left_dist = pos.x - self.min.x;
         let top_dist = pos.y - self.min.y;
         let right_dist = self.max.x - pos.x;
@@ -76,123 +329,1061 @@ impl Bounds {
 pub struct Simulation {
     swarms: Vec<Swarm>,
     config: SimulationConfig,
-    ship_config: Rc<ShipConfig>,
-    swarm_config: Rc<SwarmConfig>,
+    ship_config: Arc<ShipConfig>,
+    swarm_config: Arc<SwarmConfig>,
     bounds: Bounds,
+    rng: Xoshiro256PlusPlus,
+    ship_ids: ShipIdAllocator,
+    next_swarm_id: u64,
+    /// stats of swarms that have been eliminated or merged into others, in order
+    eliminated: Vec<(SwarmId, SwarmStats)>,
+    tick: u64,
+    /// ticks left until the next respawn, counts down while below max_swarms
+    respawn_cooldown: u32,
+    beacons: Vec<Beacon>,
+    next_beacon_id: u64,
+    /// ticks left until the next beacon spawns, counts down while below max_beacons
+    beacon_cooldown: u32,
+    /// events that have not been drained yet
+    events: Vec<Event>,
+    subscribers: Vec<EventCallback>,
+    brain_factory: Option<BrainFactory>,
+    /// spread steps over all cores
+    #[cfg(feature = "parallel")]
+    parallel: bool,
 }
 
 impl Simulation {
+    /// Create a simulation using the default ship and swarm configs
     pub fn new(config: SimulationConfig, bounds: Bounds) -> Simulation {
-        // TODO: replace with input configs
-        let ship_config = Rc::new(ShipConfig::default());
-        let swarm_config = Rc::new(SwarmConfig::default());
+        Simulation::with_configs(
+            config,
+            bounds,
+            ShipConfig::default(),
+            SwarmConfig::default(),
+        )
+    }
 
+    pub fn with_configs(
+        config: SimulationConfig,
+        bounds: Bounds,
+        ship_config: ShipConfig,
+        swarm_config: SwarmConfig,
+    ) -> Simulation {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(config.seed);
+        let mut bounds = bounds;
+        bounds.zone = config.zone.shape.map(|shape| {
+            let center = bounds.random_pos(&mut rng, config.zone.margin);
+            let scale = config.zone.scale_at(0);
+            Zone::new(shape, center, scale, bounds.min, bounds.max)
+        });
         Simulation {
             swarms: vec![],
-            config: SimulationConfig {
-                max_swarms: config.max_swarms,
-                init_swarms: config.init_swarms,
-            },
-            ship_config,
-            swarm_config,
+            rng,
+            respawn_cooldown: config.respawn_interval,
+            beacon_cooldown: config.beacons.spawn_interval,
+            config,
+            ship_config: Arc::new(ship_config),
+            swarm_config: Arc::new(swarm_config),
             bounds,
+            ship_ids: ShipIdAllocator::default(),
+            next_swarm_id: 0,
+            eliminated: vec![],
+            tick: 0,
+            beacons: vec![],
+            next_beacon_id: 0,
+            events: vec![],
+            subscribers: vec![],
+            brain_factory: None,
+            #[cfg(feature = "parallel")]
+            parallel: false,
+        }
+    }
+
+    /// Capture the complete simulation state
+    pub fn snapshot(&self) -> SimulationSnapshot {
+        SimulationSnapshot {
+            version: SNAPSHOT_VERSION,
+            config: self.config.clone(),
+            ship_config: (*self.ship_config).clone(),
+            swarm_config: (*self.swarm_config).clone(),
+            bounds: self.bounds.clone(),
+            rng: self.rng.clone(),
+            ship_ids: self.ship_ids.clone(),
+            next_swarm_id: self.next_swarm_id,
+            tick: self.tick,
+            respawn_cooldown: self.respawn_cooldown,
+            eliminated: self.eliminated.clone(),
+            swarms: self.swarms.iter().map(Swarm::snapshot).collect(),
+            brain_factory: self.brain_factory.is_some(),
+            beacons: self.beacons.clone(),
+            next_beacon_id: self.next_beacon_id,
+            beacon_cooldown: self.beacon_cooldown,
         }
     }
 
+    /// Recreate a simulation that only uses the default brain from a
+    /// snapshot, see `from_snapshot_with_brains`
+    pub fn from_snapshot(snapshot: SimulationSnapshot) -> Result<Simulation, SnapshotError> {
+        Simulation::from_snapshot_with_brains(snapshot, &BrainRegistry::default())
+    }
+
+    /// Recreate a simulation from a snapshot, without events or subscribers.
+    /// Swarms get the brain registered under the name of their old brain, and
+    /// the brain factory comes from `brains` if the simulation had one.
+    pub fn from_snapshot_with_brains(
+        snapshot: SimulationSnapshot,
+        brains: &BrainRegistry,
+    ) -> Result<Simulation, SnapshotError> {
+        let brain_factory = match brains.factory() {
+            Some(factory) if snapshot.brain_factory => Some(Arc::clone(factory)),
+            None if snapshot.brain_factory => return Err(SnapshotError::MissingBrainFactory),
+            _ => None,
+        };
+        let ship_config = Arc::new(snapshot.ship_config);
+        let swarm_config = Arc::new(snapshot.swarm_config);
+        let swarms = snapshot
+            .swarms
+            .iter()
+            .map(|swarm| {
+                let brain = brains
+                    .get(&swarm.brain)
+                    .ok_or_else(|| SnapshotError::UnknownBrain(swarm.brain.clone()))?;
+                Ok(Swarm::from_snapshot(
+                    swarm,
+                    brain,
+                    Arc::clone(&swarm_config),
+                    Arc::clone(&ship_config),
+                ))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Simulation {
+            swarms,
+            config: snapshot.config,
+            ship_config,
+            swarm_config,
+            bounds: snapshot.bounds,
+            rng: snapshot.rng,
+            ship_ids: snapshot.ship_ids,
+            next_swarm_id: snapshot.next_swarm_id,
+            eliminated: snapshot.eliminated,
+            tick: snapshot.tick,
+            respawn_cooldown: snapshot.respawn_cooldown,
+            beacons: snapshot.beacons,
+            next_beacon_id: snapshot.next_beacon_id,
+            beacon_cooldown: snapshot.beacon_cooldown,
+            events: vec![],
+            subscribers: vec![],
+            brain_factory,
+            #[cfg(feature = "parallel")]
+            parallel: false,
+        })
+    }
+
+    pub fn config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
     pub fn swarms(&self) -> &[Swarm] {
         &self.swarms
     }
 
+    pub fn swarm(&self, id: SwarmId) -> Option<&Swarm> {
+        self.swarms.iter().find(|swarm| swarm.id == id)
+    }
+
+    pub fn swarm_mut(&mut self, id: SwarmId) -> Option<&mut Swarm> {
+        self.swarms.iter_mut().find(|swarm| swarm.id == id)
+    }
+
+    pub fn beacons(&self) -> &[Beacon] {
+        &self.beacons
+    }
+
     pub fn bounds(&self) -> &Bounds {
         &self.bounds
     }
 
-    /// Spawn a new swarm at the given position, returns its index
-    pub fn spawn_swarm(&mut self, pos: Vec2, num_ships: u32) -> usize {
-        let swarm = Swarm::spawn(
+    pub fn ship_config(&self) -> &ShipConfig {
+        &self.ship_config
+    }
+
+    pub fn swarm_config(&self) -> &SwarmConfig {
+        &self.swarm_config
+    }
+
+    /// Number of steps performed so far
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Stats of all swarms that have been eliminated or merged into another
+    /// swarm (see `SwarmStats::merged_into`), in order of elimination
+    pub fn eliminated(&self) -> &[(SwarmId, SwarmStats)] {
+        &self.eliminated
+    }
+
+    /// Take all events recorded since the last call. Events accumulate until
+    /// drained, so long running callers should drain regularly.
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, Event> {
+        self.events.drain(..)
+    }
+
+    /// Call `callback` for every future event, as soon as it happens.
+    /// Subscribing does not stop events from being recorded for `drain_events`.
+    pub fn subscribe(&mut self, callback: impl FnMut(&Event) + Send + Sync + 'static) {
+        self.subscribers.push(Box::new(callback));
+    }
+
+    /// Use `factory` to pick the brain of every swarm spawned from now on,
+    /// instead of the default brain. Factories only get the swarm ID, so a
+    /// restored snapshot spawns the same brains again.
+    pub fn set_brain_factory(
+        &mut self,
+        factory: impl Fn(SwarmId) -> Arc<dyn SwarmBrain> + Send + Sync + 'static,
+    ) {
+        self.brain_factory = Some(Arc::new(factory));
+    }
+
+    fn emit(&mut self, kind: EventKind) {
+        let event = Event {
+            tick: self.tick,
+            kind,
+        };
+        for subscriber in &mut self.subscribers {
+            subscriber(&event);
+        }
+        self.events.push(event);
+    }
+
+    /// Spawn a swarm with a size drawn from the configured distribution
+    /// at a position picked by the given policy, returns its id
+    pub fn spawn_random_swarm(&mut self, location: SpawnLocation) -> SwarmId {
+        let num_ships = self.config.swarm_size.sample(&mut self.rng);
+        let pos = location.pick(&self.bounds, &mut self.rng, &self.swarms);
+        let team = self.smallest_team();
+        self.spawn_team_swarm(pos, num_ships, team)
+    }
+
+    /// Team with the fewest swarms alive, lowest id first. None without teams.
+    fn smallest_team(&self) -> Option<TeamId> {
+        (0..self.config.teams).map(TeamId).min_by_key(|team| {
+            self.swarms
+                .iter()
+                .filter(|swarm| swarm.team == Some(*team))
+                .count()
+        })
+    }
+
+    /// Number of opposing sides left: each team counts once, swarms without a
+    /// team count once per lineage
+    pub fn sides_left(&self) -> usize {
+        let teams: HashSet<TeamId> = self.swarms.iter().filter_map(|swarm| swarm.team).collect();
+        let loners: HashSet<SwarmId> = self
+            .swarms
+            .iter()
+            .filter(|swarm| swarm.team.is_none())
+            .map(|swarm| swarm.lineage)
+            .collect();
+        teams.len() + loners.len()
+    }
+
+    /// Spawn random swarms until init_swarms is reached
+    pub fn spawn_initial_swarms(&mut self) {
+        while self.swarms.len() < self.config.init_swarms as usize {
+            self.spawn_random_swarm(self.config.init_location);
+        }
+    }
+
+    /// Count down the respawn timer while below max_swarms, spawn a swarm when it expires
+    fn respawn(&mut self) {
+        if self.config.respawn_interval == 0 || self.swarms.len() >= self.config.max_swarms as usize
+        {
+            self.respawn_cooldown = self.config.respawn_interval;
+            return;
+        }
+
+        self.respawn_cooldown = self.respawn_cooldown.saturating_sub(1);
+        if self.respawn_cooldown == 0 {
+            self.spawn_random_swarm(self.config.respawn_location);
+            self.respawn_cooldown = self.config.respawn_interval;
+        }
+    }
+
+    /// Spawn a new swarm without a team at the given position, returns its id
+    pub fn spawn_swarm(&mut self, pos: Vec2, num_ships: u32) -> SwarmId {
+        self.spawn_team_swarm(pos, num_ships, None)
+    }
+
+    /// Spawn a new swarm at the given position as part of `team`, returns its id.
+    /// The swarm gets at most max_ships ships.
+    pub fn spawn_team_swarm(&mut self, pos: Vec2, num_ships: u32, team: Option<TeamId>) -> SwarmId {
+        let id = SwarmId(self.next_swarm_id);
+        self.next_swarm_id += 1;
+
+        let personality = self.swarm_config.personality.sample(&mut self.rng);
+        let mut swarm = Swarm::spawn(
+            id,
             pos,
             num_ships,
-            Rc::clone(&self.swarm_config),
-            Rc::clone(&self.ship_config),
+            personality,
+            &mut self.ship_ids,
+            Arc::clone(&self.swarm_config),
+            Arc::clone(&self.ship_config),
         );
+        swarm.team = team;
+        // cells of half the vision range, so a swarm sees a few cells at once
+        let cell_size = (self.swarm_config.vision_range / 2.0).max(1.0);
+        swarm.exploration = Arc::new(ExplorationMap::new(&self.bounds, cell_size));
+        if let Some(factory) = &self.brain_factory {
+            swarm.brain = factory(id);
+        }
         self.swarms.push(swarm);
-        self.swarms.len() - 1
+        self.emit(EventKind::SwarmSpawned {
+            swarm: id,
+            pos,
+            num_ships,
+            team,
+            personality,
+        });
+        id
     }
 
-    /// Get swarm indices, positions, and distances within vision range, sorted by distance
-    pub fn get_swarms_in_range(&self, swarm_idx: usize) -> Vec<(&Swarm, f32)> {
-        let swarm = &self.swarms[swarm_idx];
-        let range_sq = self.swarm_config.vision_range * self.swarm_config.vision_range;
-        let mut in_range = Vec::new();
+    /// Split `num_ships` ships off the left flank of a swarm into a new swarm,
+    /// see `Swarm::split_off`. Both halves stay allies, with or without a team.
+    /// Returns the id of the new swarm,
+    /// None if the swarm doesn't exist or would be left without ships.
+    pub fn split_swarm(&mut self, id: SwarmId, num_ships: u32) -> Option<SwarmId> {
+        let new_id = SwarmId(self.next_swarm_id);
+        let bounds = self.bounds.clone();
+        let swarm = self.swarm_mut(id)?;
+        if num_ships == 0 || num_ships >= swarm.num_ships() {
+            return None;
+        }
+        let new_swarm = swarm.split_off(new_id, num_ships, &bounds);
+        self.next_swarm_id += 1;
+        self.swarms.push(new_swarm);
+        self.emit(EventKind::SwarmSplit {
+            swarm: id,
+            new_swarm: new_id,
+            num_ships,
+        });
+        Some(new_id)
+    }
 
-        for (idx, other) in self.swarms.iter().enumerate() {
-            if idx == swarm_idx {
-                continue;
+    /// Merge swarm `other` into `into`, see `Swarm::absorb`. Only allies within
+    /// vision range of each other can merge, and only while the result stays
+    /// within max_ships. Returns false and changes nothing otherwise.
+    pub fn merge_swarms(&mut self, into: SwarmId, other: SwarmId) -> bool {
+        let Some(into_idx) = self.swarms.iter().position(|swarm| swarm.id == into) else {
+            return false;
+        };
+        let Some(other_idx) = self.swarms.iter().position(|swarm| swarm.id == other) else {
+            return false;
+        };
+        let (a, b) = (&self.swarms[into_idx], &self.swarms[other_idx]);
+        if into == other
+            || !a.is_ally(b)
+            || self.bounds.distance(a.center, b.center) > self.swarm_config.vision_range
+            || a.num_ships() + b.num_ships() > self.swarm_config.max_ships
+        {
+            return false;
+        }
+
+        let mut merged = self.swarms.remove(other_idx);
+        let num_ships = merged.num_ships();
+        let bounds = self.bounds.clone();
+        self.swarm_mut(into).unwrap().absorb(&mut merged, &bounds);
+        self.eliminated.push((other, merged.stats));
+        self.emit(EventKind::SwarmsMerged {
+            swarm: into,
+            merged: other,
+            num_ships,
+        });
+        true
+    }
+
+    /// Count down the beacon timer while below max_beacons, spawn a beacon when it expires
+    fn spawn_beacons(&mut self) {
+        let config = &self.config.beacons;
+        if config.spawn_interval == 0 || self.beacons.len() >= config.max_beacons as usize {
+            self.beacon_cooldown = config.spawn_interval;
+            return;
+        }
+
+        self.beacon_cooldown = self.beacon_cooldown.saturating_sub(1);
+        if self.beacon_cooldown == 0 {
+            let payload = config.payload.sample(&mut self.rng);
+            let pos = self.bounds.random_pos(&mut self.rng, config.margin);
+            self.spawn_beacon(pos, payload);
+            self.beacon_cooldown = self.config.beacons.spawn_interval;
+        }
+    }
+
+    /// Place a beacon at the given position, returns its id
+    pub fn spawn_beacon(&mut self, pos: Vec2, payload: u32) -> BeaconId {
+        let id = BeaconId(self.next_beacon_id);
+        self.next_beacon_id += 1;
+
+        let lifetime = self.config.beacons.lifetime;
+        self.beacons.push(Beacon {
+            id,
+            pos,
+            payload,
+            expires_at: (lifetime > 0).then(|| self.tick + lifetime as u64),
+            capture: None,
+        });
+        self.emit(EventKind::BeaconSpawned {
+            beacon: id,
+            pos,
+            payload,
+        });
+        id
+    }
+
+    /// Resize the zone according to the schedule
+    fn update_zone(&mut self) {
+        if let Some(zone) = self.bounds.zone {
+            let scale = self.config.zone.scale_at(self.tick);
+            self.bounds.zone = Some(Zone::new(
+                zone.shape(),
+                zone.center(),
+                scale,
+                self.bounds.min,
+                self.bounds.max,
+            ));
+        }
+    }
+
+    /// Every damage_interval ticks, ships outside the zone lose health. Ships
+    /// destroyed this way have no killer and are removed when finalizing.
+    fn zone_damage(&mut self, events: &mut Vec<EventKind>) {
+        let config = &self.config.zone;
+        let Some(zone) = self.bounds.zone else {
+            return;
+        };
+        let (interval, damage) = (config.damage_interval, config.damage);
+        self.damage_outside(
+            interval,
+            damage,
+            |pos| zone.contains(pos),
+            |swarm, ship, health_left| EventKind::ZoneDamage {
+                swarm,
+                ship,
+                health_left,
+            },
+            DestroyCause::Zone,
+            events,
+        );
+    }
+
+    /// With `Boundary::Damage`, ships outside the walls lose health like
+    /// ships outside the zone
+    fn wall_damage(&mut self, events: &mut Vec<EventKind>) {
+        let Boundary::Damage { interval, damage } = self.bounds.boundary else {
+            return;
+        };
+        let bounds = self.bounds.clone();
+        self.damage_outside(
+            interval,
+            damage,
+            |pos| bounds.contains(pos),
+            |swarm, ship, health_left| EventKind::WallDamage {
+                swarm,
+                ship,
+                health_left,
+            },
+            DestroyCause::Walls,
+            events,
+        );
+    }
+
+    /// Every `interval` ticks, living ships at positions not `inside` lose `damage`
+    /// health. Ships that lose their last health point are destroyed by `cause`.
+    fn damage_outside(
+        &mut self,
+        interval: u32,
+        damage: u32,
+        inside: impl Fn(Vec2) -> bool,
+        event: impl Fn(SwarmId, ShipId, u32) -> EventKind,
+        cause: DestroyCause,
+        events: &mut Vec<EventKind>,
+    ) {
+        if interval == 0 || !self.tick.is_multiple_of(interval as u64) {
+            return;
+        }
+        for swarm in &mut self.swarms {
+            for (ship, _) in &mut swarm.ships {
+                if ship.health == 0 || inside(ship.pos) {
+                    continue;
+                }
+                ship.health = ship.health.saturating_sub(damage);
+                events.push(event(swarm.id, ship.id, ship.health));
+                if ship.health == 0 {
+                    events.push(EventKind::ShipDestroyed {
+                        swarm: swarm.id,
+                        ship: ship.id,
+                        cause,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Progress captures by the nearest swarm within capture_radius, hand out
+    /// ships of completed captures and remove expired beacons
+    fn update_beacons(&mut self) {
+        let radius_sq = self.config.beacons.capture_radius * self.config.beacons.capture_radius;
+        let mut events = Vec::new();
+        let mut remaining = Vec::new();
+
+        for mut beacon in std::mem::take(&mut self.beacons) {
+            // keep the first swarm on ties, so results only depend on swarm order
+            let mut holder: Option<(SwarmId, f32)> = None;
+            for swarm in &self.swarms {
+                let dist_sq = self.bounds.distance_squared(swarm.center, beacon.pos);
+                if dist_sq <= radius_sq && holder.is_none_or(|(_, best)| dist_sq < best) {
+                    holder = Some((swarm.id, dist_sq));
+                }
             }
-            let dist_sq = swarm.center.distance_squared(other.center);
-            if dist_sq <= range_sq {
-                in_range.push((other, dist_sq.sqrt()));
+
+            // progress is lost as soon as another swarm gets closer or the holder leaves
+            beacon.capture = holder.map(|(swarm, _)| match beacon.capture {
+                Some(capture) if capture.swarm == swarm => Capture {
+                    swarm,
+                    progress: capture.progress + 1,
+                },
+                _ => Capture { swarm, progress: 1 },
+            });
+
+            if let Some(capture) = beacon.capture
+                && capture.progress >= self.config.beacons.capture_ticks
+            {
+                let ship_config = Arc::clone(&self.ship_config);
+                let swarm = self.swarms.iter_mut().find(|s| s.id == capture.swarm);
+                let ships_added =
+                    swarm
+                        .unwrap()
+                        .add_ships(beacon.payload, &mut self.ship_ids, ship_config);
+                events.push(EventKind::BeaconCaptured {
+                    beacon: beacon.id,
+                    swarm: capture.swarm,
+                    ships_added,
+                });
+            } else if beacon.expires_at.is_some_and(|tick| tick <= self.tick) {
+                events.push(EventKind::BeaconExpired { beacon: beacon.id });
+            } else {
+                remaining.push(beacon);
             }
         }
 
-        in_range.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        self.beacons = remaining;
+        for kind in events {
+            self.emit(kind);
+        }
+    }
+
+    /// Get other swarms and their distances within vision range, sorted by distance
+    pub fn get_swarms_in_range(&self, swarm_id: SwarmId) -> Vec<(&Swarm, f32)> {
+        let Some(swarm) = self.swarm(swarm_id) else {
+            return Vec::new();
+        };
+        let range = self.swarm_config.vision_range;
+        // swarm centers only, ships don't matter here
+        let mut grid = SpatialGrid::new(range);
+        for (idx, other) in self.swarms.iter().enumerate() {
+            grid.insert(other.center, idx);
+        }
+        let mut nearby: Vec<usize> = self
+            .bounds
+            .images(swarm.center, range)
+            .into_iter()
+            .flat_map(|pos| grid.query(pos, range))
+            .filter(|idx| self.swarms[*idx].id != swarm_id)
+            .collect();
+        nearby.sort_unstable();
+        nearby.dedup();
+        let mut in_range: Vec<(&Swarm, f32)> = nearby
+            .into_iter()
+            .map(|idx| &self.swarms[idx])
+            .map(|other| (other, self.bounds.distance(swarm.center, other.center)))
+            .collect();
+        in_range.sort_by(|a, b| a.1.total_cmp(&b.1));
         in_range
     }
 
+    /// Build what the given swarm can currently see, allies share their vision.
+    /// On a torus all positions are the copies closest to the swarm's center.
+    pub fn observe(&self, swarm_id: SwarmId) -> Option<Observation> {
+        let swarm_idx = self.swarms.iter().position(|swarm| swarm.id == swarm_id)?;
+        let index = SpatialIndex::build(&self.swarms, self.swarm_config.vision_range);
+        let vision = self.shared_vision(swarm_idx, &index);
+        Some(self.observe_indexed(swarm_idx, &index, &vision))
+    }
+
+    /// Enemy swarms, ships and beacons the swarm at `swarm_idx` and its allies see
+    fn shared_vision(&self, swarm_idx: usize, index: &SpatialIndex) -> SharedVision {
+        let swarm = &self.swarms[swarm_idx];
+        let vision_range = swarm.config.vision_range;
+        let bounds = &self.bounds;
+
+        let viewers = index.viewers(&self.swarms, swarm_idx);
+        let is_enemy = |idx: &usize| viewers.binary_search(idx).is_err();
+        let centers: Vec<Vec2> = viewers.iter().map(|idx| self.swarms[*idx].center).collect();
+        let mut vision = SharedVision::new(centers.iter().copied(), vision_range);
+
+        // swarms camping for too long are revealed to everybody
+        let camping_ticks = swarm.config.camping_ticks;
+        let camping = |other: &Swarm| camping_ticks > 0 && other.idle_ticks >= camping_ticks;
+        let campers = index
+            .idle
+            .iter()
+            .copied()
+            .take_while(|idx| camping(&self.swarms[*idx]));
+        let mut candidates: Vec<usize> = centers
+            .iter()
+            .flat_map(|pos| bounds.images(*pos, vision_range))
+            .flat_map(|pos| index.swarms.query(pos, vision_range))
+            .chain(campers)
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        vision.swarms = candidates
+            .into_iter()
+            .filter(is_enemy)
+            .filter_map(|idx| {
+                let other = &self.swarms[idx];
+                match vision.sight_distance(bounds, other.center) {
+                    Some(distance) => Some((idx, distance)),
+                    // campers out of sight are still seen from the nearest viewer
+                    None if camping(other) => {
+                        let distance = centers
+                            .iter()
+                            .map(|center| bounds.distance(*center, other.center))
+                            .fold(f32::INFINITY, f32::min);
+                        Some((idx, distance))
+                    }
+                    None => None,
+                }
+            })
+            .collect();
+
+        // in swarm and formation order, no matter which viewer saw a ship
+        let mut nearby_ships: Vec<(usize, usize)> = centers
+            .iter()
+            .flat_map(|pos| bounds.images(*pos, vision_range))
+            .flat_map(|pos| index.ships.query(pos, vision_range))
+            .filter(|(idx, _)| is_enemy(idx))
+            .collect();
+        nearby_ships.sort_unstable();
+        nearby_ships.dedup();
+        vision.ships = nearby_ships
+            .into_iter()
+            .filter_map(|(idx, slot)| {
+                let ship = &self.swarms[idx].ships[slot].0;
+                let distance = vision.sight_distance(bounds, ship.pos)?;
+                Some(((idx, slot), distance))
+            })
+            .collect();
+
+        vision.beacons = (0..self.beacons.len())
+            .filter(|idx| vision.in_sight(bounds, self.beacons[*idx].pos))
+            .collect();
+        vision
+    }
+
+    /// `observe`, looking up allies in an index of the current positions and
+    /// taking what they see together from `vision`
+    fn observe_indexed(
+        &self,
+        swarm_idx: usize,
+        index: &SpatialIndex,
+        vision: &SharedVision,
+    ) -> Observation {
+        let swarm = &self.swarms[swarm_idx];
+        let swarm_id = swarm.id;
+        let vision_range = swarm.config.vision_range;
+
+        let allies: Vec<&Swarm> = index
+            .allies(&self.swarms, swarm_idx)
+            .into_iter()
+            .map(|idx| &self.swarms[idx])
+            .collect();
+        let bounds = &self.bounds;
+        let in_sight = |pos: Vec2| vision.in_sight(bounds, pos);
+        let by_distance = |a: Vec2, b: Vec2| {
+            let dist_a = bounds.distance_squared(a, swarm.center);
+            let dist_b = bounds.distance_squared(b, swarm.center);
+            dist_a.partial_cmp(&dist_b).unwrap()
+        };
+        let local = |pos: Vec2| bounds.nearest_image(swarm.center, pos);
+
+        // position error grows with the distance to the nearest viewer,
+        // up to observation_noise at the edge of vision
+        let max_noise = swarm.config.observation_noise;
+        let noisy = |pos: Vec2, distance: f32, observed: u64| {
+            if max_noise == 0.0 {
+                return pos;
+            }
+            let scale = max_noise * distance / vision_range;
+            let offset = Vec2::new(
+                noise(self.config.seed, self.tick, swarm_id.0, observed, 0),
+                noise(self.config.seed, self.tick, swarm_id.0, observed, 1),
+            );
+            pos + offset * scale
+        };
+
+        let mut seen: Vec<(&Swarm, f32)> = vision
+            .swarms
+            .iter()
+            .map(|(idx, distance)| (&self.swarms[*idx], *distance))
+            .collect();
+        seen.sort_by(|a, b| by_distance(a.0.center, b.0.center));
+        let contacts: Vec<Contact> = seen
+            .into_iter()
+            .map(|(other, distance)| {
+                // swarm and ship IDs don't overlap in the noise, swarms use the upper half
+                let center = local(noisy(other.center, distance, other.id.0 | 1 << 63));
+                Contact {
+                    swarm: other.id,
+                    center,
+                    velocity: other.velocity,
+                    num_ships: other.num_ships(),
+                    max_speed: self.ship_config.max_speed
+                        * other.growth_factor(&other.config.speed_penalty),
+                    distance: swarm.center.distance(center),
+                }
+            })
+            .collect();
+
+        let ships = vision
+            .ships
+            .iter()
+            .map(|((idx, slot), distance)| {
+                let other = &self.swarms[*idx];
+                let ship = &other.ships[*slot].0;
+                ShipSighting {
+                    id: ship.id,
+                    swarm: other.id,
+                    pos: local(noisy(ship.pos, *distance, ship.id.0)),
+                    vel: ship.vel,
+                }
+            })
+            .collect();
+
+        let mut beacons: Vec<_> = vision
+            .beacons
+            .iter()
+            .map(|idx| &self.beacons[*idx])
+            .map(|beacon| Beacon {
+                pos: local(beacon.pos),
+                ..beacon.clone()
+            })
+            .collect();
+        beacons.sort_by(|a, b| by_distance(a.pos, b.pos));
+
+        // forget beacons that are in sight again, or whose position is in sight but empty
+        let mut remembered_beacons: Vec<Beacon> = swarm
+            .remembered_beacons
+            .iter()
+            .filter(|memory| !beacons.iter().any(|beacon| beacon.id == memory.id))
+            .filter(|memory| !in_sight(memory.pos))
+            .map(|memory| Beacon {
+                pos: local(memory.pos),
+                ..memory.clone()
+            })
+            .collect();
+        remembered_beacons.sort_by(|a, b| by_distance(a.pos, b.pos));
+
+        // forget contacts that are in sight again, or whose estimated position is in sight but empty
+        let memory_ticks = swarm.config.memory_ticks as u64;
+        let remembered = swarm
+            .memory
+            .iter()
+            .filter(|memory| self.tick - memory.last_seen < memory_ticks)
+            .filter(|memory| !contacts.iter().any(|c| c.swarm == memory.swarm))
+            .filter(|memory| !in_sight(estimated_pos(memory, self.tick, &self.bounds)))
+            .cloned()
+            .collect();
+
+        let mut allies: Vec<Ally> = allies
+            .iter()
+            .map(|ally| Ally {
+                swarm: ally.id,
+                center: local(ally.center),
+                velocity: ally.velocity,
+                num_ships: ally.num_ships(),
+                target_pos: local(ally.target_pos),
+                intent: ally.intent,
+                distance: bounds.distance(swarm.center, ally.center),
+            })
+            .collect();
+        allies.sort_by(|a, b| by_distance(a.center, b.center));
+
+        Observation {
+            tick: self.tick,
+            own: OwnSwarm {
+                id: swarm.id,
+                team: swarm.team,
+                center: swarm.center,
+                velocity: swarm.velocity,
+                target_pos: local(swarm.target_pos),
+                direction: swarm.direction,
+                num_ships: swarm.num_ships(),
+                max_ships: swarm.config.max_ships,
+                occupancy: swarm.occupancy(),
+                vision_range,
+                max_speed: self.ship_config.max_speed
+                    * swarm.growth_factor(&swarm.config.speed_penalty),
+                max_accel: self.ship_config.max_accel
+                    * swarm.growth_factor(&swarm.config.accel_penalty),
+                kite_distance: swarm.config.kite_range * self.ship_config.aim_range,
+                memory_ticks: swarm.config.memory_ticks,
+                roam_interval: swarm.config.roam_interval,
+                coordination: swarm.config.coordination,
+                personality: swarm.personality,
+            },
+            bounds: self.bounds.clone(),
+            contacts,
+            allies,
+            ships,
+            beacons,
+            remembered,
+            remembered_beacons,
+            explored: Arc::clone(&swarm.exploration),
+        }
+    }
+
+    /// Enemy ships the swarm at `swarm_idx` can fight, as (swarm index, ship index)
+    /// in swarm and formation order. Only ships in aim range of one of the swarm's
+    /// ships matter, so only those around the formation are looked at.
+    fn enemy_ships(&self, swarm_idx: usize, index: &SpatialIndex) -> Vec<(usize, usize)> {
+        let swarm = &self.swarms[swarm_idx];
+        let bounds = &self.bounds;
+        let spread = swarm
+            .ships
+            .iter()
+            .map(|(ship, _)| bounds.distance(swarm.center, ship.pos))
+            .fold(0.0, f32::max);
+        let range = spread + self.ship_config.aim_range;
+        let mut nearby: Vec<(usize, usize)> = bounds
+            .images(swarm.center, range)
+            .into_iter()
+            .flat_map(|pos| index.ships.query(pos, range))
+            .filter(|(idx, _)| *idx != swarm_idx)
+            .filter(|(idx, _)| {
+                let other = &self.swarms[*idx];
+                !swarm.is_ally(other)
+                    && bounds.distance(other.center, swarm.center) <= swarm.config.vision_range
+            })
+            .collect();
+        nearby.sort_unstable();
+        nearby.dedup();
+        nearby
+    }
+
+    /// Spread every step over all cores. Steps give bit-identical results
+    /// either way, only brains and event subscribers need to be thread safe.
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    /// Call `f` for every swarm and its index, results are in swarm order.
+    /// Runs on all cores with `set_parallel`.
+    fn map_swarms<T: Send>(&self, f: impl Fn(usize, &Swarm) -> T + Send + Sync) -> Vec<T> {
+        #[cfg(feature = "parallel")]
+        if self.parallel {
+            use rayon::prelude::*;
+            return self
+                .swarms
+                .par_iter()
+                .enumerate()
+                .map(|(idx, swarm)| f(idx, swarm))
+                .collect();
+        }
+        self.swarms
+            .iter()
+            .enumerate()
+            .map(|(idx, swarm)| f(idx, swarm))
+            .collect()
+    }
+
+    /// Like `map_swarms`, with each swarm borrowed mutably
+    fn map_swarms_mut<T: Send>(
+        &mut self,
+        f: impl Fn(usize, &mut Swarm) -> T + Send + Sync,
+    ) -> Vec<T> {
+        #[cfg(feature = "parallel")]
+        if self.parallel {
+            use rayon::prelude::*;
+            return self
+                .swarms
+                .par_iter_mut()
+                .enumerate()
+                .map(|(idx, swarm)| f(idx, swarm))
+                .collect();
+        }
+        self.swarms
+            .iter_mut()
+            .enumerate()
+            .map(|(idx, swarm)| f(idx, swarm))
+            .collect()
+    }
+
     /// Perform one update of the simulation
     pub fn step(&mut self) {
-        // Phase 1: Collect decisions (read-only)
-        let decisions: Vec<Option<SwarmDecision>> = (0..self.swarms.len())
-            .map(|idx| self.swarms[idx].decide(self, idx))
-            .collect();
+        // Phase 0: Shrink the zone, respawn swarms and spawn beacons
+        self.update_zone();
+        self.respawn();
+        self.spawn_beacons();
+
+        // Phase 1: Observe and collect decisions (read-only)
+        let index = SpatialIndex::build(&self.swarms, self.swarm_config.vision_range);
+        // allies see together, so each group's vision is built once by its first swarm
+        let groups = index.vision_groups(&self.swarms);
+        let visions: Vec<Option<SharedVision>> =
+            self.map_swarms(|idx, _| (groups[idx] == idx).then(|| self.shared_vision(idx, &index)));
+        let decisions: Vec<(Observation, Option<SwarmDecision>)> = self.map_swarms(|idx, swarm| {
+            let vision = visions[groups[idx]].as_ref();
+            let vision = vision.expect("the first swarm of a group builds its vision");
+            let observation = self.observe_indexed(idx, &index, vision);
+            let decision = swarm.brain.decide(&observation);
+            (observation, decision)
+        });
 
-        // Phase 2: Apply decisions
-        for (swarm, decision) in self.swarms.iter_mut().zip(decisions) {
-            if let Some(d) = decision {
+        // Phase 2: Apply decisions and remember what was seen
+        for (swarm, (observation, decision)) in self.swarms.iter_mut().zip(decisions) {
+            if let Some(mut d) = decision {
+                // on a torus, take the short way to the target
+                d.target = self.bounds.nearest_image(swarm.center, d.target);
                 swarm.apply_decision(&d);
             }
+            swarm.memory = observation.memory();
+            swarm.remembered_beacons = observation.beacon_memory();
+            // the observation shares the exploration map, let go of it to update in place
+            drop(observation);
+            let vision_range = swarm.config.vision_range;
+            let exploration = Arc::make_mut(&mut swarm.exploration);
+            for pos in self.bounds.images(swarm.center, vision_range) {
+                exploration.mark_seen(pos, vision_range, self.tick);
+            }
         }
 
         // Phase 3: Movement
-        for swarm in &mut self.swarms {
-            swarm.movement();
+        let bounds = self.bounds.clone();
+        let contacts = self.map_swarms_mut(|_, swarm| {
+            let mut events = Vec::new();
+            swarm.movement(&bounds, &mut events);
+            events
+        });
+        for event in contacts.into_iter().flatten() {
+            self.emit(event);
         }
 
-        // Phase 4: Combat, each swarm fights nearby enemy ships
-        let mut all_hits: Vec<ShipId> = Vec::new();
+        // Phase 4: Beacon captures
+        self.update_beacons();
 
-        for swarm_idx in 0..self.swarms.len() {
-            let (before, rest) = self.swarms.split_at_mut(swarm_idx);
-            let (swarm, after) = rest.split_first_mut().unwrap();
-
-            let enemies: Vec<&Ship> = before
+        // Phase 5: Combat, each swarm fights nearby enemy ships, allies are never targeted
+        let index = SpatialIndex::build(&self.swarms, self.swarm_config.vision_range);
+        let targets: Vec<(Vec<(usize, usize)>, f32)> = self.map_swarms(|swarm_idx, swarm| {
+            // capturing a beacon makes a swarm vulnerable
+            let capturing = self.beacons.iter().any(|b| b.is_captured_by(swarm.id));
+            let lock_factor = if capturing {
+                self.config.beacons.capture_lock_factor
+            } else {
+                1.0
+            };
+            (self.enemy_ships(swarm_idx, &index), lock_factor)
+        });
+        // ships don't move while fighting, so every swarm can fight against the same copy
+        let ships: Vec<Vec<Ship>> = self
+            .swarms
+            .iter()
+            .map(|swarm| swarm.ships.iter().map(|(ship, _)| ship.clone()).collect())
+            .collect();
+        let fights = self.map_swarms_mut(|swarm_idx, swarm| {
+            let (slots, lock_factor) = &targets[swarm_idx];
+            let enemies: Vec<&Ship> = slots
                 .iter()
-                .chain(after.iter())
-                .filter(|other| other.center.distance(swarm.center) <= swarm.config.vision_range)
-                .flat_map(|s| s.ships.iter().map(|(ship, _)| ship))
+                .map(|(idx, slot)| &ships[*idx][*slot])
                 .collect();
+            let mut events = Vec::new();
+            let hits = swarm.fight(&enemies, *lock_factor, &bounds, &mut events);
+            swarm.stats.shots_fired += hits.len() as u32;
+            (hits, events)
+        });
 
-            let hits = swarm.fight(&enemies);
-            all_hits.extend(hits);
+        // (shooter swarm index, shooter, hit ship)
+        let mut all_hits: Vec<(usize, ShipId, ShipId)> = Vec::new();
+        let mut events: Vec<EventKind> = Vec::new();
+        for (swarm_idx, (hits, swarm_events)) in fights.into_iter().enumerate() {
+            all_hits.extend(
+                hits.into_iter()
+                    .map(|(shooter, hit_id)| (swarm_idx, shooter, hit_id)),
+            );
+            events.extend(swarm_events);
         }
 
-        // apply damage
-        for hit_id in &all_hits {
-            for swarm in &mut self.swarms {
-                for (ship, _) in &mut swarm.ships {
-                    if ship.id == *hit_id {
-                        ship.health = ship.health.saturating_sub(1);
-                    }
-                }
+        // apply damage, the shot that takes the last health point gets the kill
+        // swarm -> swarm that destroyed its most recently killed ship
+        let mut last_killer: HashMap<SwarmId, SwarmId> = HashMap::new();
+        for (shooter_idx, shooter, hit_id) in &all_hits {
+            let shooter_swarm = self.swarms[*shooter_idx].id;
+            let Some(&(swarm_idx, slot)) = index.ship_slots.get(hit_id) else {
+                continue;
+            };
+            let swarm = &mut self.swarms[swarm_idx];
+            let ship = &mut swarm.ships[slot].0;
+            if ship.health == 0 {
+                continue;
+            }
+            ship.health -= 1;
+            events.push(EventKind::DamageApplied {
+                swarm: swarm.id,
+                ship: ship.id,
+                attacker: *shooter,
+                health_left: ship.health,
+            });
+            if ship.health == 0 {
+                last_killer.insert(swarm.id, shooter_swarm);
+                events.push(EventKind::ShipDestroyed {
+                    swarm: swarm.id,
+                    ship: ship.id,
+                    cause: DestroyCause::Shot {
+                        killer_swarm: shooter_swarm,
+                        killer: *shooter,
+                    },
+                });
+                self.swarms[*shooter_idx].stats.kills += 1;
             }
         }
+        self.zone_damage(&mut events);
+        self.wall_damage(&mut events);
 
-        // Phase 5: Finalize
-        for swarm in &mut self.swarms {
-            swarm.finalize();
-        }
+        // Phase 6: Finalize
+        self.map_swarms_mut(|_, swarm| {
+            swarm.finalize(&bounds);
+            swarm.stats.ticks_alive += 1;
+        });
 
         // cleanup dead swarms
-        self.swarms.retain(|s| !s.ships.is_empty());
+        let (alive, dead): (Vec<Swarm>, Vec<Swarm>) =
+            self.swarms.drain(..).partition(|s| !s.ships.is_empty());
+        self.swarms = alive;
+        for swarm in dead {
+            events.push(EventKind::SwarmEliminated {
+                swarm: swarm.id,
+                killer_swarm: last_killer.get(&swarm.id).copied(),
+            });
+            self.eliminated.push((swarm.id, swarm.stats));
+        }
+
+        for kind in events {
+            self.emit(kind);
+        }
+
+        self.tick += 1;
     }
 }
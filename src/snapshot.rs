@@ -0,0 +1,248 @@
+use std::fmt;
+use std::path::Path;
+
+use glam::Vec2;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::beacon::Beacon;
+use crate::exploration::ExplorationMap;
+use crate::observation::RememberedContact;
+use crate::personality::SwarmPersonality;
+use crate::ship::{ShipConfig, ShipId, ShipIdAllocator};
+use crate::simulation::{Bounds, SimulationConfig};
+use crate::spawner::{SpawnLocation, SwarmSize};
+use crate::stats::SwarmStats;
+use crate::swarm::{Intent, SwarmConfig, SwarmId, TeamId};
+
+/// Bumped whenever the snapshot layout changes, old snapshots are rejected
+pub const SNAPSHOT_VERSION: u32 = 15;
+
+/// Snapshot encoding of `SpawnLocation`. Postcard can't decode the internally
+/// tagged scenario format, so binary formats use this externally tagged copy.
+#[derive(Serialize, Deserialize)]
+enum SpawnLocationSnapshot {
+    Edge,
+    Interior { margin: f32 },
+    FarthestFromEnemies { margin: f32, candidates: u32 },
+}
+
+impl From<SpawnLocation> for SpawnLocationSnapshot {
+    fn from(location: SpawnLocation) -> Self {
+        match location {
+            SpawnLocation::Edge => SpawnLocationSnapshot::Edge,
+            SpawnLocation::Interior { margin } => SpawnLocationSnapshot::Interior { margin },
+            SpawnLocation::FarthestFromEnemies { margin, candidates } => {
+                SpawnLocationSnapshot::FarthestFromEnemies { margin, candidates }
+            }
+        }
+    }
+}
+
+impl From<SpawnLocationSnapshot> for SpawnLocation {
+    fn from(location: SpawnLocationSnapshot) -> Self {
+        match location {
+            SpawnLocationSnapshot::Edge => SpawnLocation::Edge,
+            SpawnLocationSnapshot::Interior { margin } => SpawnLocation::Interior { margin },
+            SpawnLocationSnapshot::FarthestFromEnemies { margin, candidates } => {
+                SpawnLocation::FarthestFromEnemies { margin, candidates }
+            }
+        }
+    }
+}
+
+impl Serialize for SpawnLocation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            SpawnLocation::serialize(self, serializer)
+        } else {
+            SpawnLocationSnapshot::from(*self).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SpawnLocation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            SpawnLocation::deserialize(deserializer)
+        } else {
+            SpawnLocationSnapshot::deserialize(deserializer).map(SpawnLocation::from)
+        }
+    }
+}
+
+/// Snapshot encoding of `SwarmSize`, see `SpawnLocationSnapshot`
+#[derive(Serialize, Deserialize)]
+enum SwarmSizeSnapshot {
+    Fixed { ships: u32 },
+    Uniform { min: u32, max: u32 },
+}
+
+impl From<SwarmSize> for SwarmSizeSnapshot {
+    fn from(size: SwarmSize) -> Self {
+        match size {
+            SwarmSize::Fixed { ships } => SwarmSizeSnapshot::Fixed { ships },
+            SwarmSize::Uniform { min, max } => SwarmSizeSnapshot::Uniform { min, max },
+        }
+    }
+}
+
+impl From<SwarmSizeSnapshot> for SwarmSize {
+    fn from(size: SwarmSizeSnapshot) -> Self {
+        match size {
+            SwarmSizeSnapshot::Fixed { ships } => SwarmSize::Fixed { ships },
+            SwarmSizeSnapshot::Uniform { min, max } => SwarmSize::Uniform { min, max },
+        }
+    }
+}
+
+impl Serialize for SwarmSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            SwarmSize::serialize(self, serializer)
+        } else {
+            SwarmSizeSnapshot::from(*self).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SwarmSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            SwarmSize::deserialize(deserializer)
+        } else {
+            SwarmSizeSnapshot::deserialize(deserializer).map(SwarmSize::from)
+        }
+    }
+}
+
+/// Complete state of a single ship, without its shared config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShipSnapshot {
+    pub id: ShipId,
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub target_pos: Vec2,
+    pub health: u32,
+    pub lock_target: Option<ShipId>,
+    pub lock_progress: u32,
+    pub fired_at: Option<Vec2>,
+    pub lock_target_pos: Option<Vec2>,
+    pub lock_time: u32,
+}
+
+/// Complete state of a single swarm, without its shared config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwarmSnapshot {
+    pub id: SwarmId,
+    pub team: Option<TeamId>,
+    pub lineage: SwarmId,
+    /// ships and their relative positions
+    pub ships: Vec<(ShipSnapshot, Vec2)>,
+    pub target_pos: Vec2,
+    pub direction: f32,
+    pub center: Vec2,
+    pub velocity: Vec2,
+    pub stats: SwarmStats,
+    pub personality: SwarmPersonality,
+    pub memory: Vec<RememberedContact>,
+    pub remembered_beacons: Vec<Beacon>,
+    pub exploration: ExplorationMap,
+    pub idle_ticks: u32,
+    pub intent: Intent,
+    /// `SwarmBrain::name` of the swarm's brain
+    pub brain: String,
+    pub prev_center: Vec2,
+    pub target_direction: f32,
+}
+
+/// Complete state of a simulation. Restoring a snapshot and stepping gives
+/// exactly the same results as stepping the original simulation.
+/// Pending events and subscribers are not part of the snapshot, brains are
+/// only stored by name (see `Simulation::from_snapshot_with_brains`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationSnapshot {
+    pub version: u32,
+    pub config: SimulationConfig,
+    pub ship_config: ShipConfig,
+    pub swarm_config: SwarmConfig,
+    pub bounds: Bounds,
+    pub rng: Xoshiro256PlusPlus,
+    pub ship_ids: ShipIdAllocator,
+    pub next_swarm_id: u64,
+    pub tick: u64,
+    pub respawn_cooldown: u32,
+    pub eliminated: Vec<(SwarmId, SwarmStats)>,
+    pub swarms: Vec<SwarmSnapshot>,
+    /// whether new swarms got their brain from a brain factory
+    pub brain_factory: bool,
+    pub beacons: Vec<Beacon>,
+    pub next_beacon_id: u64,
+    pub beacon_cooldown: u32,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// snapshot file could not be read or written
+    Io(std::io::Error),
+    /// snapshot bytes could not be encoded or decoded
+    Encoding(postcard::Error),
+    /// snapshot was written by an incompatible version
+    Version(u32),
+    /// a swarm's brain is not in the `BrainRegistry` it was restored with
+    UnknownBrain(String),
+    /// the simulation had a brain factory, but the `BrainRegistry` has none
+    MissingBrainFactory,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "snapshot io failed: {err}"),
+            SnapshotError::Encoding(err) => write!(f, "malformed snapshot: {err}"),
+            SnapshotError::Version(version) => write!(
+                f,
+                "snapshot version {version} is not supported, expected {SNAPSHOT_VERSION}"
+            ),
+            SnapshotError::UnknownBrain(name) => {
+                write!(f, "snapshot uses brain \"{name}\", which is not registered")
+            }
+            SnapshotError::MissingBrainFactory => {
+                write!(f, "snapshot needs a brain factory, but none is registered")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Checks the version that encoded snapshots and replays start with before
+/// decoding the rest, whose layout may have changed since
+pub(crate) fn check_version(bytes: &[u8]) -> Result<(), SnapshotError> {
+    let (version, _) = postcard::take_from_bytes::<u32>(bytes).map_err(SnapshotError::Encoding)?;
+    if version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::Version(version));
+    }
+    Ok(())
+}
+
+impl SimulationSnapshot {
+    /// Encode into a compact binary representation, floats are stored bit-exact
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnapshotError> {
+        postcard::to_stdvec(self).map_err(SnapshotError::Encoding)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<SimulationSnapshot, SnapshotError> {
+        check_version(bytes)?;
+        postcard::from_bytes(bytes).map_err(SnapshotError::Encoding)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        std::fs::write(path, self.to_bytes()?).map_err(SnapshotError::Io)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<SimulationSnapshot, SnapshotError> {
+        let bytes = std::fs::read(path).map_err(SnapshotError::Io)?;
+        SimulationSnapshot::from_bytes(&bytes)
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use glam::Vec2;
+
+use crate::ship::ShipId;
+use crate::simulation::Bounds;
+use crate::swarm::{Swarm, SwarmId, TeamId};
+
+/// Uniform grid that buckets items by position, so range queries only look
+/// at the cells around the query position instead of every item
+#[derive(Debug, Clone)]
+pub struct SpatialGrid<T> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<(Vec2, T)>>,
+}
+
+impl<T: Copy> SpatialGrid<T> {
+    /// Queries are fastest with cells about as big as the usual query range
+    pub fn new(cell_size: f32) -> SpatialGrid<T> {
+        SpatialGrid {
+            cell_size: cell_size.max(1.0),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, pos: Vec2) -> (i32, i32) {
+        let cell = (pos / self.cell_size).floor();
+        (cell.x as i32, cell.y as i32)
+    }
+
+    pub fn insert(&mut self, pos: Vec2, item: T) {
+        let cell = self.cell(pos);
+        self.cells.entry(cell).or_default().push((pos, item));
+    }
+
+    /// All items within `range` of `pos`, in no particular order
+    pub fn query(&self, pos: Vec2, range: f32) -> impl Iterator<Item = T> + '_ {
+        let (min_x, min_y) = self.cell(pos - range);
+        let (max_x, max_y) = self.cell(pos + range);
+        let range_sq = range * range;
+        (min_x..=max_x)
+            .flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |(item_pos, _)| item_pos.distance_squared(pos) <= range_sq)
+            .map(|(_, item)| *item)
+    }
+}
+
+/// Where all swarms and ships are during a step. Swarms are referred to by
+/// their index in the simulation, ships by (swarm index, index in the swarm).
+/// Only valid until swarms or ships are added, removed or moved.
+pub(crate) struct SpatialIndex {
+    pub swarms: SpatialGrid<usize>,
+    pub ships: SpatialGrid<(usize, usize)>,
+    pub ship_slots: HashMap<ShipId, (usize, usize)>,
+    /// swarms standing still, the longest idle first
+    pub idle: Vec<usize>,
+    teams: HashMap<TeamId, Vec<usize>>,
+    lineages: HashMap<SwarmId, Vec<usize>>,
+}
+
+impl SpatialIndex {
+    pub fn build(swarms: &[Swarm], cell_size: f32) -> SpatialIndex {
+        let mut index = SpatialIndex {
+            swarms: SpatialGrid::new(cell_size),
+            ships: SpatialGrid::new(cell_size),
+            ship_slots: HashMap::new(),
+            idle: Vec::new(),
+            teams: HashMap::new(),
+            lineages: HashMap::new(),
+        };
+        for (swarm_idx, swarm) in swarms.iter().enumerate() {
+            index.swarms.insert(swarm.center, swarm_idx);
+            for (slot, (ship, _)) in swarm.ships.iter().enumerate() {
+                index.ships.insert(ship.pos, (swarm_idx, slot));
+                index.ship_slots.insert(ship.id, (swarm_idx, slot));
+            }
+            if swarm.idle_ticks > 0 {
+                index.idle.push(swarm_idx);
+            }
+            if let Some(team) = swarm.team {
+                index.teams.entry(team).or_default().push(swarm_idx);
+            }
+            index
+                .lineages
+                .entry(swarm.lineage)
+                .or_default()
+                .push(swarm_idx);
+        }
+        index
+            .idle
+            .sort_by_key(|idx| std::cmp::Reverse(swarms[*idx].idle_ticks));
+        index
+    }
+
+    /// Allies of the swarm at `swarm_idx`, see `Swarm::is_ally`, in swarm order
+    pub fn allies(&self, swarms: &[Swarm], swarm_idx: usize) -> Vec<usize> {
+        let swarm = &swarms[swarm_idx];
+        let team = swarm.team.and_then(|team| self.teams.get(&team));
+        let lineage = self.lineages.get(&swarm.lineage);
+        let mut allies: Vec<usize> = team
+            .into_iter()
+            .chain(lineage)
+            .flatten()
+            .copied()
+            .filter(|idx| *idx != swarm_idx)
+            .collect();
+        allies.sort_unstable();
+        allies.dedup();
+        allies
+    }
+
+    /// The swarm at `swarm_idx` and its allies, in swarm order
+    pub fn viewers(&self, swarms: &[Swarm], swarm_idx: usize) -> Vec<usize> {
+        let mut viewers = self.allies(swarms, swarm_idx);
+        viewers.insert(viewers.partition_point(|idx| *idx < swarm_idx), swarm_idx);
+        viewers
+    }
+
+    /// For every swarm the first swarm that sees exactly what it sees. Allies
+    /// share their vision, so swarms with the same allies and vision settings
+    /// can share one `SharedVision`.
+    pub fn vision_groups(&self, swarms: &[Swarm]) -> Vec<usize> {
+        let mut firsts: HashMap<(Vec<usize>, u32, u32), usize> = HashMap::new();
+        (0..swarms.len())
+            .map(|swarm_idx| {
+                let viewers = self.viewers(swarms, swarm_idx);
+                let config = &swarms[swarm_idx].config;
+                let key = (viewers, config.vision_range.to_bits(), config.camping_ticks);
+                *firsts.entry(key).or_insert(swarm_idx)
+            })
+            .collect()
+    }
+}
+
+/// What a swarm and its allies see together during a step, see
+/// `SpatialIndex::vision_groups`. Enemies are listed in index order with the
+/// distance to the nearest viewer.
+pub(crate) struct SharedVision {
+    pub viewers: SpatialGrid<Vec2>,
+    pub vision_range: f32,
+    /// enemy swarms in sight or camping
+    pub swarms: Vec<(usize, f32)>,
+    /// enemy ships in sight, as (swarm index, ship index)
+    pub ships: Vec<((usize, usize), f32)>,
+    /// indices of the beacons in sight
+    pub beacons: Vec<usize>,
+}
+
+impl SharedVision {
+    /// Viewers sharing a vision, with cells as big as their vision range
+    pub fn new(centers: impl IntoIterator<Item = Vec2>, vision_range: f32) -> SharedVision {
+        let mut viewers = SpatialGrid::new(vision_range);
+        for center in centers {
+            viewers.insert(center, center);
+        }
+        SharedVision {
+            viewers,
+            vision_range,
+            swarms: Vec::new(),
+            ships: Vec::new(),
+            beacons: Vec::new(),
+        }
+    }
+
+    /// Distance from `pos` to the nearest viewer, None if it is out of sight
+    pub fn sight_distance(&self, bounds: &Bounds, pos: Vec2) -> Option<f32> {
+        // a little extra range, so rounding never hides a viewer right at the edge
+        let range = self.vision_range + 1.0;
+        bounds
+            .images(pos, range)
+            .into_iter()
+            .flat_map(|image| self.viewers.query(image, range))
+            .map(|viewer| bounds.distance(viewer, pos))
+            .reduce(f32::min)
+            .filter(|distance| *distance <= self.vision_range)
+    }
+
+    pub fn in_sight(&self, bounds: &Bounds, pos: Vec2) -> bool {
+        self.sight_distance(bounds, pos).is_some()
+    }
+}
@@ -0,0 +1,88 @@
+use glam::Vec2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::Bounds;
+use crate::swarm::Swarm;
+
+/// Where new swarms enter the arena. Scenarios write it internally tagged,
+/// e.g. `{ kind = "interior", margin = 100.0 }`, snapshots use their own
+/// encoding (see `snapshot::SpawnLocationSnapshot`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(
+    remote = "Self",
+    tag = "kind",
+    rename_all = "snake_case",
+    deny_unknown_fields
+)]
+pub enum SpawnLocation {
+    /// random position on one of the arena walls
+    Edge,
+    /// random position inside the arena, keeping a margin to the walls
+    Interior { margin: f32 },
+    /// best of several random interior candidates, the one farthest from all other swarms
+    FarthestFromEnemies { margin: f32, candidates: u32 },
+}
+
+impl SpawnLocation {
+    pub fn pick(&self, bounds: &Bounds, rng: &mut impl Rng, swarms: &[Swarm]) -> Vec2 {
+        match *self {
+            SpawnLocation::Edge => bounds.random_edge_pos(rng),
+            SpawnLocation::Interior { margin } => bounds.random_pos(rng, margin),
+            SpawnLocation::FarthestFromEnemies { margin, candidates } => {
+                let nearest_swarm_dist_sq = |pos: Vec2| {
+                    swarms
+                        .iter()
+                        .map(|swarm| bounds.distance_squared(swarm.center, pos))
+                        .fold(f32::MAX, f32::min)
+                };
+
+                // keep the first candidate on ties, so results only depend on the rng
+                let mut best = bounds.random_pos(rng, margin);
+                let mut best_dist_sq = nearest_swarm_dist_sq(best);
+                for _ in 1..candidates {
+                    let pos = bounds.random_pos(rng, margin);
+                    let dist_sq = nearest_swarm_dist_sq(pos);
+                    if dist_sq > best_dist_sq {
+                        best = pos;
+                        best_dist_sq = dist_sq;
+                    }
+                }
+                best
+            }
+        }
+    }
+}
+
+/// Distribution of the number of ships in newly spawned swarms, written
+/// like `SpawnLocation`, e.g. `{ kind = "fixed", ships = 12 }`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(
+    remote = "Self",
+    tag = "kind",
+    rename_all = "snake_case",
+    deny_unknown_fields
+)]
+pub enum SwarmSize {
+    /// always the same number of ships
+    Fixed { ships: u32 },
+    /// uniformly distributed in `min..=max`
+    Uniform { min: u32, max: u32 },
+}
+
+impl SwarmSize {
+    pub fn sample(&self, rng: &mut impl Rng) -> u32 {
+        match *self {
+            SwarmSize::Fixed { ships } => ships,
+            SwarmSize::Uniform { min, max } => rng.random_range(min..=max),
+        }
+    }
+
+    /// Smallest and largest possible swarm size
+    pub fn range(&self) -> (u32, u32) {
+        match *self {
+            SwarmSize::Fixed { ships } => (ships, ships),
+            SwarmSize::Uniform { min, max } => (min, max),
+        }
+    }
+}
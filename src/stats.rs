@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::swarm::SwarmId;
+
+/// Running totals for a single swarm, updated by the simulation every step
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SwarmStats {
+    /// number of ships the swarm was spawned with, 0 for swarms split off
+    /// another swarm, their ships stay counted there
+    pub spawned_ships: u32,
+    /// ships added after spawning, e.g. by capturing beacons
+    pub reinforcements: u32,
+    /// number of ticks the swarm has been alive
+    pub ticks_alive: u32,
+    /// shots fired by all ships of the swarm
+    pub shots_fired: u32,
+    /// enemy ships destroyed by shots of this swarm
+    pub kills: u32,
+    /// swarm this one split off from
+    pub parent: Option<SwarmId>,
+    /// swarm this one merged into, its ships live on there
+    pub merged_into: Option<SwarmId>,
+}
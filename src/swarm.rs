@@ -1,15 +1,69 @@
 use std::collections::HashMap;
 
 use glam::Vec2;
-use std::rc::Rc;
-
-use crate::repulsion::RepulsionMap;
-use crate::ship::{Ship, ShipConfig, ShipId};
-use crate::simulation::Simulation;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::beacon::Beacon;
+use crate::brain::{RepulsionBrain, SwarmBrain};
+use crate::event::EventKind;
+use crate::exploration::ExplorationMap;
+use crate::observation::RememberedContact;
+use crate::personality::{PersonalityConfig, SwarmPersonality};
+use crate::ship::{Ship, ShipConfig, ShipId, ShipIdAllocator};
+use crate::simulation::{Bounds, Simulation};
+use crate::snapshot::SwarmSnapshot;
+use crate::stats::SwarmStats;
 
 const GOLDEN_ANGLE: f32 = 2.399_963_1;
+/// swarms moving slower than this per tick count as standing still
+const IDLE_SPEED: f32 = 0.5;
+
+/// Position of the n-th ship in the spiral layout using golden angle (sunflower structure)
+fn sunflower_slot(n: u32, scale: f32) -> Vec2 {
+    let n = n as f32;
+    Vec2::new(
+        f32::sqrt(n) * f32::cos(n * GOLDEN_ANGLE) * scale,
+        f32::sqrt(n) * f32::sin(n * GOLDEN_ANGLE) * scale,
+    )
+}
+
+/// Angle in -PI..=PI
+fn wrap_angle(angle: f32) -> f32 {
+    let angle = angle.rem_euclid(std::f32::consts::TAU);
+    if angle > std::f32::consts::PI {
+        angle - std::f32::consts::TAU
+    } else {
+        angle
+    }
+}
+
+/// Multiplier that depends on swarm size, going from 1.0 for a single ship
+/// to `at_max` at max_ships: `1 + (at_max - 1) * occupancy^exponent`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GrowthCurve {
+    /// multiplier at max_ships (1.0 = no penalty)
+    pub at_max: f32,
+    /// curve shape, 1.0 = linear, > 1.0 = penalty mostly hits large swarms
+    pub exponent: f32,
+}
 
-#[derive(Clone)]
+impl GrowthCurve {
+    /// 1.0 at every size
+    pub const NONE: GrowthCurve = GrowthCurve {
+        at_max: 1.0,
+        exponent: 1.0,
+    };
+
+    /// `occupancy` is 0.0 for a single ship and 1.0 at max_ships
+    pub fn factor(&self, occupancy: f32) -> f32 {
+        1.0 + (self.at_max - 1.0) * occupancy.powf(self.exponent)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct SwarmConfig {
     /// maximum number of ships in a swarm
     pub max_ships: u32,
@@ -20,8 +74,41 @@ pub struct SwarmConfig {
     /// maximum vision range to see other swarms
     pub vision_range: f32,
 
-    /// minimum accel/decel multiplier at max swarm size (1.0 = no penalty, 0.4 = 40% accel at max_ships)
-    pub min_accel_factor: f32,
+    /// maximum formation rotation per tick in radians (PI = turns at once)
+    pub max_turn_rate: f32,
+
+    /// accel/decel multiplier by swarm size (at_max 0.4 = 40% accel at max_ships)
+    pub accel_penalty: GrowthCurve,
+
+    /// ship top speed multiplier by swarm size
+    pub speed_penalty: GrowthCurve,
+
+    /// turn rate multiplier by swarm size
+    pub turn_penalty: GrowthCurve,
+
+    /// lock time multiplier by swarm size (at_max 1.5 = locks take 50% longer at max_ships)
+    pub lock_penalty: GrowthCurve,
+
+    /// ticks a swarm remembers contacts after losing sight of them (0 = no memory)
+    pub memory_ticks: u32,
+
+    /// maximum position error of observed swarms and ships at the edge of vision range
+    pub observation_noise: f32,
+
+    /// distributions the personality of new swarms is drawn from
+    pub personality: PersonalityConfig,
+
+    /// allies hunt the same prey and flee together
+    pub coordination: bool,
+
+    /// distance kept from slower threats when kiting them, relative to the ship aim_range (0 = never kite)
+    pub kite_range: f32,
+
+    /// ticks between new wander targets while nothing is in sight (0 = no roaming)
+    pub roam_interval: u32,
+
+    /// swarms standing still this many ticks are revealed to everybody (0 = never)
+    pub camping_ticks: u32,
 }
 
 impl Default for SwarmConfig {
@@ -30,88 +117,405 @@ impl Default for SwarmConfig {
             max_ships: 30,
             scale: 10.0,
             vision_range: 500.0,
-            min_accel_factor: 0.5,
+            max_turn_rate: std::f32::consts::PI,
+            accel_penalty: GrowthCurve {
+                at_max: 0.5,
+                exponent: 1.0,
+            },
+            speed_penalty: GrowthCurve::NONE,
+            turn_penalty: GrowthCurve::NONE,
+            lock_penalty: GrowthCurve::NONE,
+            memory_ticks: 180,
+            observation_noise: 0.0,
+            personality: PersonalityConfig::default(),
+            coordination: true,
+            kite_range: 1.1,
+            roam_interval: 300,
+            camping_ticks: 0,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SwarmId(pub u64);
+
+/// Swarms of the same team are allied, swarms without a team fight everybody
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TeamId(pub u32);
+
 /// Swarm consisting of multiple ships.
 /// Ships that are part of the swarm are assigned a position releative to
 /// the Swarms target position.
 /// The swarms own position is the average position of all ships
 pub struct Swarm {
+    /// stable identity, unlike the swarm's index in the simulation
+    pub id: SwarmId,
+    pub team: Option<TeamId>,
+    /// id of the swarm this one descends from through splits, its own id if it
+    /// wasn't split off. Swarms of the same lineage are allies.
+    pub lineage: SwarmId,
     /// keeps track of all ships and their **relative** position to swarm's target position
     pub ships: Vec<(Ship, Vec2)>,
     pub target_pos: Vec2,
+    /// current formation heading, turns towards the target with limited turn rate
     pub direction: f32,
     pub center: Vec2,
-    pub config: Rc<SwarmConfig>,
+    pub config: Arc<SwarmConfig>,
     /// Track current movement velocity for momentum penalty
     pub velocity: Vec2,
+    pub stats: SwarmStats,
+    /// decision making parameters, fixed for the lifetime of the swarm
+    pub personality: SwarmPersonality,
+    /// swarms seen before, updated from the swarm's observations
+    pub memory: Vec<RememberedContact>,
+    /// beacons seen before that are out of sight now
+    pub remembered_beacons: Vec<Beacon>,
+    /// when each part of the arena was last seen, shared with the swarm's observations
+    pub exploration: Arc<ExplorationMap>,
+    /// ticks the swarm has been standing still
+    pub idle_ticks: u32,
+    /// intent of the last decision
+    pub intent: Intent,
+    /// decision making strategy, defaults to `RepulsionBrain`
+    pub brain: Arc<dyn SwarmBrain>,
     prev_center: Vec2,
+    /// heading towards the target position, set by `set_target`
+    target_direction: f32,
+}
+
+/// What a swarm is trying to achieve by moving to its target
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Intent {
+    /// go to the target, e.g. to hunt prey or capture a beacon
+    #[default]
+    Move,
+    /// get away from threats
+    Flee,
+    /// circle `enemy` at `distance` without stopping, so its locks take longer
+    Standoff { enemy: SwarmId, distance: f32 },
 }
 
 #[derive(Debug)]
 pub struct SwarmDecision {
     pub target: Vec2,
-    pub is_threat: bool,
+    pub intent: Intent,
 }
 
 impl Swarm {
-    /// Spawn a new swarm with n ships at a given location
+    /// Spawn a new swarm with n ships at a given location, at most max_ships
     pub fn spawn(
+        id: SwarmId,
         pos: Vec2,
         num_ships: u32,
-        swarm_config: Rc<SwarmConfig>,
-        ship_config: Rc<ShipConfig>,
+        personality: SwarmPersonality,
+        ship_ids: &mut ShipIdAllocator,
+        swarm_config: Arc<SwarmConfig>,
+        ship_config: Arc<ShipConfig>,
     ) -> Swarm {
         let mut ships = Vec::new();
 
-        // spawn ships in spiral layout using golden angle (sunflower structure)
-        for ship_idx in 0..num_ships {
-            let n = ship_idx as f32;
-            let x_local = f32::sqrt(n) * f32::cos(n * GOLDEN_ANGLE) * swarm_config.scale;
-            let y_local = f32::sqrt(n) * f32::sin(n * GOLDEN_ANGLE) * swarm_config.scale;
+        for ship_idx in 0..num_ships.min(swarm_config.max_ships) {
+            let relative_pos = sunflower_slot(ship_idx, swarm_config.scale);
             let ship = Ship::spawn(
-                Vec2::new(pos.x + x_local, pos.y + y_local),
-                Rc::clone(&ship_config),
+                ship_ids.next_id(),
+                pos + relative_pos,
+                Arc::clone(&ship_config),
             );
-            ships.push((ship, Vec2::new(x_local, y_local)));
+            ships.push((ship, relative_pos));
         }
 
         Swarm {
+            id,
+            team: None,
+            lineage: id,
             ships,
             target_pos: pos,
             center: pos,
             direction: 0.0,
             config: swarm_config,
             velocity: Vec2::ZERO,
+            stats: SwarmStats {
+                spawned_ships: num_ships,
+                ..Default::default()
+            },
+            personality,
+            memory: Vec::new(),
+            remembered_beacons: Vec::new(),
+            exploration: Arc::default(),
+            idle_ticks: 0,
+            intent: Intent::Move,
+            brain: Arc::new(RepulsionBrain),
             prev_center: pos,
+            target_direction: 0.0,
+        }
+    }
+
+    pub fn snapshot(&self) -> SwarmSnapshot {
+        SwarmSnapshot {
+            id: self.id,
+            team: self.team,
+            lineage: self.lineage,
+            ships: self
+                .ships
+                .iter()
+                .map(|(ship, relative_pos)| (ship.snapshot(), *relative_pos))
+                .collect(),
+            target_pos: self.target_pos,
+            direction: self.direction,
+            center: self.center,
+            velocity: self.velocity,
+            stats: self.stats.clone(),
+            personality: self.personality,
+            memory: self.memory.clone(),
+            remembered_beacons: self.remembered_beacons.clone(),
+            exploration: (*self.exploration).clone(),
+            idle_ticks: self.idle_ticks,
+            intent: self.intent,
+            brain: self.brain.name().to_string(),
+            prev_center: self.prev_center,
+            target_direction: self.target_direction,
+        }
+    }
+
+    pub fn from_snapshot(
+        snapshot: &SwarmSnapshot,
+        brain: Arc<dyn SwarmBrain>,
+        swarm_config: Arc<SwarmConfig>,
+        ship_config: Arc<ShipConfig>,
+    ) -> Swarm {
+        Swarm {
+            id: snapshot.id,
+            team: snapshot.team,
+            lineage: snapshot.lineage,
+            ships: snapshot
+                .ships
+                .iter()
+                .map(|(ship, relative_pos)| {
+                    (
+                        Ship::from_snapshot(ship, Arc::clone(&ship_config)),
+                        *relative_pos,
+                    )
+                })
+                .collect(),
+            target_pos: snapshot.target_pos,
+            direction: snapshot.direction,
+            center: snapshot.center,
+            config: swarm_config,
+            velocity: snapshot.velocity,
+            stats: snapshot.stats.clone(),
+            personality: snapshot.personality,
+            memory: snapshot.memory.clone(),
+            remembered_beacons: snapshot.remembered_beacons.clone(),
+            exploration: Arc::new(snapshot.exploration.clone()),
+            idle_ticks: snapshot.idle_ticks,
+            intent: snapshot.intent,
+            brain,
+            prev_center: snapshot.prev_center,
+            target_direction: snapshot.target_direction,
         }
     }
 
+    /// Move the formation towards `pos`. The formation turns to face the
+    /// target over the next ticks, limited by the turn rate.
     pub fn set_target(&mut self, pos: Vec2) {
         let to_target = pos - self.center;
+        self.target_direction = to_target.y.atan2(to_target.x);
+        self.target_pos = pos;
+        self.update_ship_targets();
+    }
+
+    fn update_ship_targets(&mut self) {
+        for (ship, relative_pos) in &mut self.ships {
+            ship.set_target(self.target_pos + *relative_pos);
+        }
+    }
+
+    /// Rotate the formation towards the target direction by at most the turn rate
+    fn turn(&mut self) {
+        let max_turn = self.config.max_turn_rate * self.growth_factor(&self.config.turn_penalty);
+        let turn = wrap_angle(self.target_direction - self.direction).clamp(-max_turn, max_turn);
+        if turn == 0.0 {
+            return;
+        }
 
-        // rotate all relative positions
-        let new_direction = to_target.y.atan2(to_target.x);
-        let rotation = Vec2::from_angle(new_direction - self.direction);
+        let rotation = Vec2::from_angle(turn);
         for (_, relative_pos) in &mut self.ships {
             *relative_pos = relative_pos.rotate(rotation);
         }
-        self.direction = new_direction;
+        self.direction = wrap_angle(self.direction + turn);
+        self.update_ship_targets();
+    }
 
-        // update ship global target positions
-        self.target_pos = pos;
-        for (ship, relative_pos) in &mut self.ships {
-            ship.set_target(pos + *relative_pos);
+    /// Both swarms are in the same team or split off the same swarm
+    pub fn is_ally(&self, other: &Swarm) -> bool {
+        self.lineage == other.lineage || (self.team.is_some() && self.team == other.team)
+    }
+
+    /// Swarm size relative to max_ships, 0.0 for a single ship and 1.0 at max_ships
+    pub fn occupancy(&self) -> f32 {
+        let num_ships = self.ships.len() as f32;
+        let max_ships = self.config.max_ships as f32;
+        ((num_ships - 1.0) / (max_ships - 1.0).max(1.0)).clamp(0.0, 1.0)
+    }
+
+    /// Current value of a growth penalty curve for this swarm
+    pub fn growth_factor(&self, curve: &GrowthCurve) -> f32 {
+        curve.factor(self.occupancy())
+    }
+
+    /// Add ships to free slots of the sunflower layout, without exceeding
+    /// max_ships. Returns the number of ships actually added.
+    pub fn add_ships(
+        &mut self,
+        num_ships: u32,
+        ship_ids: &mut ShipIdAllocator,
+        ship_config: Arc<ShipConfig>,
+    ) -> u32 {
+        let free = self.config.max_ships.saturating_sub(self.num_ships());
+        let num_ships = num_ships.min(free);
+
+        // slots of destroyed ships are reused first, so the formation stays compact
+        let rotation = Vec2::from_angle(self.direction);
+        let min_dist = self.config.scale * 0.25;
+        let mut slot = 0;
+        for _ in 0..num_ships {
+            let relative_pos = loop {
+                let pos = sunflower_slot(slot, self.config.scale).rotate(rotation);
+                slot += 1;
+                let taken = self
+                    .ships
+                    .iter()
+                    .any(|(_, other)| other.distance(pos) < min_dist);
+                if !taken {
+                    break pos;
+                }
+            };
+
+            let mut ship = Ship::spawn(
+                ship_ids.next_id(),
+                self.center + relative_pos,
+                Arc::clone(&ship_config),
+            );
+            ship.set_target(self.target_pos + relative_pos);
+            self.ships.push((ship, relative_pos));
+        }
+
+        self.stats.reinforcements += num_ships;
+        num_ships
+    }
+
+    /// Assign formation slots anew around the current center, ships closest to
+    /// the center take the inner slots. Ships keep their IDs, health and locks.
+    fn reform(&mut self, bounds: &Bounds) {
+        let center = self.ship_center(bounds);
+        let mut order: Vec<usize> = (0..self.ships.len()).collect();
+        order.sort_by(|a, b| {
+            let dist_a = bounds.distance_squared(self.ships[*a].0.pos, center);
+            let dist_b = bounds.distance_squared(self.ships[*b].0.pos, center);
+            dist_a.total_cmp(&dist_b)
+        });
+
+        let rotation = Vec2::from_angle(self.direction);
+        for (slot, idx) in order.into_iter().enumerate() {
+            self.ships[idx].1 = sunflower_slot(slot as u32, self.config.scale).rotate(rotation);
+        }
+        self.center = center;
+        self.prev_center = center;
+        self.update_ship_targets();
+    }
+
+    /// Move the `num_ships` ships on the left flank of the formation into a
+    /// new swarm with the given id. The new swarm keeps team, lineage,
+    /// personality, brain and everything this swarm knows, and heads parallel
+    /// to it. Its ships stay counted as spawned by this swarm.
+    pub fn split_off(&mut self, id: SwarmId, num_ships: u32, bounds: &Bounds) -> Swarm {
+        let heading = Vec2::from_angle(self.direction);
+        // stable sort, so ships on the same line keep their order
+        self.ships
+            .sort_by(|(_, a), (_, b)| heading.perp_dot(*a).total_cmp(&heading.perp_dot(*b)));
+        let ships = self.ships.split_off(self.ships.len() - num_ships as usize);
+
+        let mut swarm = Swarm {
+            id,
+            team: self.team,
+            lineage: self.lineage,
+            ships,
+            target_pos: self.target_pos,
+            direction: self.direction,
+            center: self.center,
+            config: Arc::clone(&self.config),
+            velocity: self.velocity,
+            stats: SwarmStats {
+                parent: Some(self.id),
+                ..Default::default()
+            },
+            personality: self.personality,
+            memory: self.memory.clone(),
+            remembered_beacons: self.remembered_beacons.clone(),
+            exploration: Arc::clone(&self.exploration),
+            idle_ticks: 0,
+            intent: self.intent,
+            brain: Arc::clone(&self.brain),
+            prev_center: self.center,
+            target_direction: self.target_direction,
+        };
+        // both halves keep their offset to where the swarm was heading
+        self.target_pos += bounds.delta(self.center, self.ship_center(bounds));
+        swarm.target_pos += bounds.delta(self.center, swarm.ship_center(bounds));
+        self.reform(bounds);
+        swarm.reform(bounds);
+        swarm
+    }
+
+    /// Take over all ships of `other` and everything it knows, the formation
+    /// is rebuilt around the combined center. Ships keep their IDs, health and
+    /// locks, locks on ships that became allies are dropped in the next fight.
+    pub fn absorb(&mut self, other: &mut Swarm, bounds: &Bounds) {
+        let num_ships = other.num_ships();
+        self.ships.append(&mut other.ships);
+        self.stats.reinforcements += num_ships;
+        other.stats.merged_into = Some(self.id);
+
+        // keep the most recent sighting of every swarm and beacon
+        for memory in other.memory.drain(..) {
+            match self.memory.iter_mut().find(|own| own.swarm == memory.swarm) {
+                Some(own) if own.last_seen < memory.last_seen => *own = memory,
+                Some(_) => {}
+                None => self.memory.push(memory),
+            }
+        }
+        self.memory
+            .retain(|memory| memory.swarm != self.id && memory.swarm != other.id);
+        for beacon in other.remembered_beacons.drain(..) {
+            if !self
+                .remembered_beacons
+                .iter()
+                .any(|own| own.id == beacon.id)
+            {
+                self.remembered_beacons.push(beacon);
+            }
         }
+        Arc::make_mut(&mut self.exploration).merge(&other.exploration);
+
+        self.reform(bounds);
+        self.idle_ticks = 0;
     }
 
     /// Each ship locks onto the nearest enemy in aim_range, fires after a
-    /// delay that scales with enemy speed. Returns IDs of enemies that were hit.
-    pub fn fight(&mut self, enemies: &[&Ship]) -> Vec<ShipId> {
-        let mut hits: Vec<ShipId> = Vec::new();
+    /// delay that scales with enemy speed and `lock_factor`. With
+    /// `line_of_sight` in the ship config, obstacles block locks. Lock and shot
+    /// events are appended to `events`. Returns (shooter, target) IDs of all shots fired.
+    pub fn fight(
+        &mut self,
+        enemies: &[&Ship],
+        lock_factor: f32,
+        bounds: &Bounds,
+        events: &mut Vec<EventKind>,
+    ) -> Vec<(ShipId, ShipId)> {
+        let mut hits: Vec<(ShipId, ShipId)> = Vec::new();
+        let lock_factor = lock_factor * self.growth_factor(&self.config.lock_penalty);
+        let enemies_by_id: HashMap<ShipId, &Ship> =
+            enemies.iter().map(|enemy| (enemy.id, *enemy)).collect();
 
         // count how many of our ships already target each enemy
         let mut targeted_count: HashMap<ShipId, u32> = HashMap::new();
@@ -127,33 +531,42 @@ impl Swarm {
 
             // validate existing lock
             if let Some(target_id) = ship.lock_target {
-                let target = enemies.iter().find(|enemy| enemy.id == target_id);
+                let target = enemies_by_id.get(&target_id);
 
-                let valid = target
-                    .is_some_and(|target| ship.pos.distance(target.pos) <= ship.config.aim_range);
+                let valid = target.is_some_and(|target| ship.can_aim_at(target, bounds));
 
                 if !valid {
                     *targeted_count.entry(target_id).or_default() =
                         targeted_count.get(&target_id).unwrap_or(&1) - 1;
                     ship.lock_target = None;
                     ship.lock_progress = 0;
+                    events.push(EventKind::LockLost {
+                        swarm: self.id,
+                        ship: ship.id,
+                        target: target_id,
+                    });
                 }
             }
 
             // progress existing lock or fire
             if let Some(target_id) = ship.lock_target {
-                let target = enemies.iter().find(|enemy| enemy.id == target_id).unwrap();
-                ship.lock_target_pos = Some(target.pos);
+                let target = enemies_by_id[&target_id];
+                ship.lock_target_pos = Some(bounds.nearest_image(ship.pos, target.pos));
                 ship.lock_progress += 1;
 
                 let speed_ratio = target.speed() / ship.config.max_speed;
                 let multiplier = 1.0 + speed_ratio * (ship.config.lock_time_factor - 1.0);
-                let lock_time = (ship.config.fire_delay as f32 * multiplier) as u32;
+                let lock_time = (ship.config.fire_delay as f32 * multiplier * lock_factor) as u32;
                 ship.lock_time = lock_time;
 
                 if ship.lock_progress >= lock_time {
-                    ship.fired_at = Some(target.pos);
-                    hits.push(target_id);
+                    ship.fired_at = Some(bounds.nearest_image(ship.pos, target.pos));
+                    hits.push((ship.id, target_id));
+                    events.push(EventKind::ShotFired {
+                        swarm: self.id,
+                        shooter: ship.id,
+                        target: target_id,
+                    });
 
                     // reset lock after firing
                     *targeted_count.entry(target_id).or_default() =
@@ -168,118 +581,97 @@ impl Swarm {
             let best = enemies
                 .iter()
                 .filter(|enemy| {
-                    let dist = ship.pos.distance(enemy.pos);
                     let count = targeted_count.get(&enemy.id).copied().unwrap_or(0);
-                    dist <= ship.config.aim_range && count < enemy.health
+                    count < enemy.health && ship.can_aim_at(enemy, bounds)
                 })
                 .min_by(|a, b| {
-                    let dist_a = ship.pos.distance_squared(a.pos);
-                    let dist_b = ship.pos.distance_squared(b.pos);
+                    let dist_a = bounds.distance_squared(a.pos, ship.pos);
+                    let dist_b = bounds.distance_squared(b.pos, ship.pos);
                     dist_a.partial_cmp(&dist_b).unwrap()
                 });
 
             if let Some(target) = best {
                 ship.lock_target = Some(target.id);
                 ship.lock_progress = 0;
-                ship.lock_target_pos = Some(target.pos);
+                ship.lock_target_pos = Some(bounds.nearest_image(ship.pos, target.pos));
                 *targeted_count.entry(target.id).or_default() += 1;
+                events.push(EventKind::LockAcquired {
+                    swarm: self.id,
+                    ship: ship.id,
+                    target: target.id,
+                });
             }
         }
 
         hits
     }
 
-    pub fn movement(&mut self) {
-        let num_ships = self.ships.len() as f32;
-        let max_ships = self.config.max_ships as f32;
-        let occupancy_ratio = (num_ships - 1.0) / (max_ships - 1.0).max(1.0);
-        let accel_factor =
-            (1.0 - occupancy_ratio) + (self.config.min_accel_factor * occupancy_ratio);
+    /// Moves all ships towards their formation slots, ships slide along
+    /// obstacles and wrap around the edges of a torus. Ships that hit the
+    /// arena walls are reported as `WallContact` events in `events`.
+    pub fn movement(&mut self, bounds: &Bounds, events: &mut Vec<EventKind>) {
+        self.turn();
 
+        let accel_factor = self.growth_factor(&self.config.accel_penalty);
+        let speed_factor = self.growth_factor(&self.config.speed_penalty);
         for (ship, _) in &mut self.ships {
-            ship.movement(accel_factor);
+            // on a torus, head for the closest copy of the slot
+            ship.target_pos = bounds.nearest_image(ship.pos, ship.target_pos);
+            ship.movement(accel_factor, speed_factor);
+            ship.pos = bounds.wrap(ship.pos);
+            ship.collide(&bounds.obstacles);
+            if ship.confine(bounds) {
+                events.push(EventKind::WallContact {
+                    swarm: self.id,
+                    ship: ship.id,
+                    pos: ship.pos,
+                });
+            }
         }
     }
 
-    pub fn finalize(&mut self) {
+    pub fn finalize(&mut self, bounds: &Bounds) {
         self.ships.retain(|(ship, _)| ship.health > 0);
-        let new_center =
-            self.ships.iter().map(|(s, _)| s.pos).sum::<Vec2>() / self.ships.len() as f32;
-        self.velocity = new_center - self.prev_center;
+        let new_center = self.ship_center(bounds);
+        self.velocity = bounds.delta(self.prev_center, new_center);
+        if bounds.delta(self.center, new_center).length() < IDLE_SPEED {
+            self.idle_ticks += 1;
+        } else {
+            self.idle_ticks = 0;
+        }
         self.prev_center = self.center;
         self.center = new_center;
     }
 
+    /// Average position of all ships. On a torus ships are averaged by their
+    /// offsets to the current center, so a formation across an edge stays together.
+    fn ship_center(&self, bounds: &Bounds) -> Vec2 {
+        let num_ships = self.ships.len() as f32;
+        if !bounds.wrap_around {
+            return self.ships.iter().map(|(s, _)| s.pos).sum::<Vec2>() / num_ships;
+        }
+        let offset = self
+            .ships
+            .iter()
+            .map(|(s, _)| bounds.delta(self.center, s.pos))
+            .sum::<Vec2>()
+            / num_ships;
+        bounds.wrap(self.center + offset)
+    }
+
     pub fn num_ships(&self) -> u32 {
         self.ships.len() as u32
     }
 
-    /// Make decisions based on the current simulation state
-    pub fn decide(&self, sim: &Simulation, self_idx: usize) -> Option<SwarmDecision> {
-        // Tunable constants
-        const ENEMY_SIGMA: f32 = 0.8; // ~45 degrees spread
-        const WALL_SIGMA: f32 = 0.5;
-        const VELOCITY_SIGMA: f32 = 1.0;
-        const WALL_DETECT_RANGE: f32 = 150.0;
-        const FLEE_DISTANCE: f32 = 400.0;
-        const WALL_MARGIN: f32 = 50.0;
-        const VELOCITY_PENALTY_STRENGTH: f32 = 0.3;
-        const PREY_SIZE_DIFFERENCE: u32 = 5; // must be this much smaller to be considered prey
-
-        let nearby_swarms = sim.get_swarms_in_range(self_idx);
-        let bounds = sim.bounds();
-
-        // Find if there are any threats and potential chase targets
-        let mut chase_target: Option<Vec2> = None;
-        let mut has_threat = false;
-
-        for (swarm, _dist) in &nearby_swarms {
-            if swarm.num_ships() + PREY_SIZE_DIFFERENCE >= self.num_ships() {
-                has_threat = true;
-            } else if chase_target.is_none() {
-                chase_target = Some(swarm.center);
-            }
-        }
-
-        if has_threat {
-            let mut repulsion = RepulsionMap::new();
-
-            for (enemy, dist) in &nearby_swarms {
-                if enemy.num_ships() >= self.num_ships() {
-                    let angle = (enemy.center - self.center).to_angle();
-                    // scale strength by ship count ratio and inverse distance TODO: is this good?
-                    let ship_ratio = enemy.num_ships() as f32 / self.num_ships().max(1) as f32;
-                    let dist_factor = 1.0 - (dist / self.config.vision_range).min(1.0);
-                    let strength = ship_ratio * dist_factor;
-                    repulsion.add_repulsor(angle, strength, ENEMY_SIGMA);
-                }
-            }
-            repulsion.add_wall_repulsion(self.center, bounds, WALL_DETECT_RANGE, WALL_SIGMA);
-            repulsion.add_velocity_penalty(
-                self.velocity,
-                VELOCITY_PENALTY_STRENGTH,
-                VELOCITY_SIGMA,
-            );
-
-            let best_angle = repulsion.best_angle();
-            let flee_dir = Vec2::from_angle(best_angle);
-            let target =
-                bounds.clamp_with_margin(self.center + flee_dir * FLEE_DISTANCE, WALL_MARGIN);
-
-            Some(SwarmDecision {
-                target,
-                is_threat: true,
-            })
-        } else {
-            chase_target.map(|target| SwarmDecision {
-                target,
-                is_threat: false,
-            })
-        }
+    /// Let the swarm's brain decide based on what the swarm can observe
+    pub fn decide(&self, sim: &Simulation) -> Option<SwarmDecision> {
+        let observation = sim.observe(self.id)?;
+        self.brain.decide(&observation)
     }
 
     /// Apply a decision to this swarm
     pub fn apply_decision(&mut self, decision: &SwarmDecision) {
+        self.intent = decision.intent;
         self.set_target(decision.target);
     }
 }
@@ -0,0 +1,202 @@
+use glam::Vec2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneShape {
+    Circle,
+    Rectangle,
+}
+
+/// Zone size at a tick, 1.0 covers the whole arena and 0.0 is just the zone center
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZoneStep {
+    pub tick: u64,
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ZoneConfig {
+    /// shape of the playable zone (none = the whole arena stays playable)
+    pub shape: Option<ZoneShape>,
+
+    /// zone size over time, linear between the steps. Before the first
+    /// step the zone has the first size, after the last step the last size.
+    pub schedule: Vec<ZoneStep>,
+
+    /// minimum distance of the random zone center to the walls
+    pub margin: f32,
+
+    /// ticks between damage to ships outside the zone
+    pub damage_interval: u32,
+
+    /// health points ships outside the zone lose every damage_interval
+    pub damage: u32,
+}
+
+impl Default for ZoneConfig {
+    fn default() -> Self {
+        ZoneConfig {
+            shape: None,
+            schedule: vec![
+                ZoneStep {
+                    tick: 3600,
+                    scale: 1.0,
+                },
+                ZoneStep {
+                    tick: 14400,
+                    scale: 0.1,
+                },
+            ],
+            margin: 300.0,
+            damage_interval: 60,
+            damage: 1,
+        }
+    }
+}
+
+impl ZoneConfig {
+    /// Zone size at `tick` according to the schedule, 1.0 without a schedule
+    pub fn scale_at(&self, tick: u64) -> f32 {
+        let Some(first) = self.schedule.first() else {
+            return 1.0;
+        };
+        if tick <= first.tick {
+            return first.scale;
+        }
+        for pair in self.schedule.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if tick <= to.tick {
+                let t = (tick - from.tick) as f32 / (to.tick - from.tick).max(1) as f32;
+                return from.scale + (to.scale - from.scale) * t;
+            }
+        }
+        self.schedule.last().unwrap().scale
+    }
+}
+
+/// The playable part of the arena at one tick. Ships outside of it take damage.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Zone {
+    Circle { center: Vec2, radius: f32 },
+    Rectangle { center: Vec2, half_size: Vec2 },
+}
+
+impl Zone {
+    /// Zone around `center` that covers the whole arena from `min` to `max`
+    /// at `scale` 1.0 and shrinks towards `center` for smaller scales
+    pub fn new(shape: ZoneShape, center: Vec2, scale: f32, min: Vec2, max: Vec2) -> Zone {
+        let reach = (center - min).max(max - center);
+        match shape {
+            ZoneShape::Circle => Zone::Circle {
+                center,
+                radius: reach.length() * scale,
+            },
+            ZoneShape::Rectangle => Zone::Rectangle {
+                center,
+                half_size: reach * scale,
+            },
+        }
+    }
+
+    pub fn shape(&self) -> ZoneShape {
+        match self {
+            Zone::Circle { .. } => ZoneShape::Circle,
+            Zone::Rectangle { .. } => ZoneShape::Rectangle,
+        }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        match *self {
+            Zone::Circle { center, .. } | Zone::Rectangle { center, .. } => center,
+        }
+    }
+
+    /// Corners of the smallest rectangle around the zone, (min, max)
+    pub fn bounding_box(&self) -> (Vec2, Vec2) {
+        let (center, half_size) = match *self {
+            Zone::Circle { center, radius } => (center, Vec2::splat(radius)),
+            Zone::Rectangle { center, half_size } => (center, half_size),
+        };
+        (center - half_size, center + half_size)
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        match *self {
+            Zone::Circle { center, radius } => pos.distance_squared(center) <= radius * radius,
+            Zone::Rectangle { center, half_size } => (pos - center).abs().cmple(half_size).all(),
+        }
+    }
+
+    /// Moves `pos` into the zone, at least `margin` away from its edge if the zone is big enough
+    pub fn clamp_with_margin(&self, pos: Vec2, margin: f32) -> Vec2 {
+        match *self {
+            Zone::Circle { center, radius } => {
+                let max_dist = (radius - margin).max(0.0);
+                let offset = pos - center;
+                if offset.length() <= max_dist {
+                    return pos;
+                }
+                center + offset.normalize_or_zero() * max_dist
+            }
+            Zone::Rectangle { center, half_size } => {
+                let half_size = (half_size - Vec2::splat(margin)).max(Vec2::ZERO);
+                pos.clamp(center - half_size, center + half_size)
+            }
+        }
+    }
+
+    /// Distance along the normalized direction `dir` from `pos` inside the zone
+    /// to its edge, None from outside of the zone
+    pub fn raycast(&self, pos: Vec2, dir: Vec2) -> Option<f32> {
+        if !self.contains(pos) {
+            return None;
+        }
+        match *self {
+            Zone::Circle { center, radius } => {
+                let to_pos = pos - center;
+                let b = to_pos.dot(dir);
+                let c = to_pos.length_squared() - radius * radius;
+                Some(-b + (b * b - c).max(0.0).sqrt())
+            }
+            Zone::Rectangle { center, half_size } => {
+                let to_edge = |pos: f32, center: f32, half_size: f32, dir: f32| {
+                    if dir > 0.0 {
+                        (center + half_size - pos) / dir
+                    } else if dir < 0.0 {
+                        (center - half_size - pos) / dir
+                    } else {
+                        f32::MAX
+                    }
+                };
+                let x = to_edge(pos.x, center.x, half_size.x, dir.x);
+                let y = to_edge(pos.y, center.y, half_size.y, dir.y);
+                Some(x.min(y))
+            }
+        }
+    }
+
+    /// Random position on the zone edge
+    pub fn random_edge_pos(&self, rng: &mut impl Rng) -> Vec2 {
+        match *self {
+            Zone::Circle { center, radius } => {
+                center + Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU)) * radius
+            }
+            Zone::Rectangle { center, half_size } => {
+                // every point on the outline is equally likely
+                let along = rng.random_range(-1.0..=1.0);
+                let horizontal = half_size.x / (half_size.x + half_size.y).max(f32::EPSILON);
+                let side = if rng.random_bool(0.5) { 1.0 } else { -1.0 };
+                let offset = if rng.random_bool(horizontal as f64) {
+                    Vec2::new(along * half_size.x, side * half_size.y)
+                } else {
+                    Vec2::new(side * half_size.x, along * half_size.y)
+                };
+                center + offset
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::EmptySim;
+use glam::Vec2;
+use swarm_simulation::beacon::BeaconConfig;
+use swarm_simulation::event::EventKind;
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::spawner::SwarmSize;
+
+/// Empty simulation with the given beacon settings
+fn beacon_sim(beacons: BeaconConfig) -> Simulation {
+    EmptySim {
+        config: SimulationConfig {
+            max_swarms: 4,
+            beacons,
+            ..EmptySim::default().config
+        },
+        bounds: Bounds::new(2000.0, 2000.0),
+        ..Default::default()
+    }
+    .build()
+}
+
+#[test]
+fn holding_a_beacon_captures_it() {
+    let config = BeaconConfig {
+        spawn_interval: 0,
+        capture_ticks: 30,
+        ..Default::default()
+    };
+    let mut sim = beacon_sim(config);
+    let swarm = sim.spawn_swarm(Vec2::new(1000.0, 1000.0), 5);
+    let beacon = sim.spawn_beacon(Vec2::new(1000.0, 1000.0), 4);
+
+    for _ in 0..29 {
+        sim.step();
+    }
+    assert_eq!(sim.beacons()[0].capture.unwrap().progress, 29);
+    assert!(sim.beacons()[0].is_captured_by(swarm));
+
+    sim.step();
+    assert!(sim.beacons().is_empty());
+    assert_eq!(sim.swarm(swarm).unwrap().num_ships(), 9);
+    assert_eq!(sim.swarm(swarm).unwrap().stats.reinforcements, 4);
+
+    let captured: Vec<_> = sim
+        .drain_events()
+        .filter(|event| matches!(event.kind, EventKind::BeaconCaptured { .. }))
+        .collect();
+    assert_eq!(captured.len(), 1);
+    assert_eq!(
+        captured[0].kind,
+        EventKind::BeaconCaptured {
+            beacon,
+            swarm,
+            ships_added: 4
+        }
+    );
+}
+
+#[test]
+fn idle_swarms_move_to_visible_beacons() {
+    let mut sim = beacon_sim(BeaconConfig {
+        spawn_interval: 0,
+        ..Default::default()
+    });
+    let swarm = sim.spawn_swarm(Vec2::new(600.0, 1000.0), 5);
+    sim.spawn_beacon(Vec2::new(1000.0, 1000.0), 2);
+
+    for _ in 0..600 {
+        sim.step();
+    }
+    assert!(sim.beacons().is_empty());
+    assert_eq!(sim.swarm(swarm).unwrap().num_ships(), 7);
+}
+
+#[test]
+fn unused_beacons_expire() {
+    let mut sim = beacon_sim(BeaconConfig {
+        spawn_interval: 0,
+        lifetime: 50,
+        ..Default::default()
+    });
+    let beacon = sim.spawn_beacon(Vec2::new(1000.0, 1000.0), 2);
+
+    for _ in 0..50 {
+        sim.step();
+    }
+    assert_eq!(sim.beacons().len(), 1);
+    sim.step();
+    assert!(sim.beacons().is_empty());
+    assert!(
+        sim.drain_events()
+            .any(|event| event.kind == EventKind::BeaconExpired { beacon })
+    );
+}
+
+#[test]
+fn beacons_spawn_up_to_max_beacons() {
+    let mut sim = beacon_sim(BeaconConfig {
+        spawn_interval: 10,
+        max_beacons: 3,
+        lifetime: 0,
+        payload: SwarmSize::Uniform { min: 2, max: 6 },
+        ..Default::default()
+    });
+
+    for _ in 0..9 {
+        sim.step();
+    }
+    assert!(sim.beacons().is_empty());
+    sim.step();
+    assert_eq!(sim.beacons().len(), 1);
+
+    for _ in 0..100 {
+        sim.step();
+    }
+    assert_eq!(sim.beacons().len(), 3);
+    for beacon in sim.beacons() {
+        assert!((2..=6).contains(&beacon.payload));
+        assert!(beacon.pos.cmpge(Vec2::splat(100.0)).all());
+        assert!(beacon.pos.cmple(Vec2::splat(1900.0)).all());
+    }
+}
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use glam::Vec2;
+use swarm_simulation::boundary::Boundary;
+use swarm_simulation::event::{DestroyCause, EventKind};
+use swarm_simulation::scenario::Scenario;
+use swarm_simulation::ship::{ShipConfig, ShipIdAllocator};
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::swarm::{Swarm, SwarmConfig, SwarmId};
+
+/// Swarm next to the left wall, heading straight through it
+fn swarm_into_the_wall() -> Swarm {
+    let config = SwarmConfig::default();
+    let mut swarm = Swarm::spawn(
+        SwarmId(0),
+        Vec2::new(60.0, 500.0),
+        8,
+        config.personality.midpoint(),
+        &mut ShipIdAllocator::default(),
+        Arc::new(config),
+        Arc::new(ShipConfig::default()),
+    );
+    swarm.set_target(Vec2::new(-500.0, 500.0));
+    swarm
+}
+
+#[test]
+fn walls_clamp_or_bounce_ships() {
+    let (min, max) = (Vec2::ZERO, Vec2::new(100.0, 100.0));
+    let inside = (Vec2::new(50.0, 50.0), Vec2::new(-3.0, 2.0));
+    for boundary in [
+        Boundary::Open,
+        Boundary::Clamp,
+        Boundary::Bounce,
+        Boundary::Damage {
+            interval: 1,
+            damage: 1,
+        },
+    ] {
+        assert_eq!(
+            boundary.apply(min, max, inside.0, inside.1),
+            (inside.0, inside.1, false)
+        );
+    }
+
+    let (pos, vel) = (Vec2::new(-2.0, 50.0), Vec2::new(-3.0, 2.0));
+    assert_eq!(Boundary::Open.apply(min, max, pos, vel), (pos, vel, false));
+    assert_eq!(
+        Boundary::Clamp.apply(min, max, pos, vel),
+        (Vec2::new(0.0, 50.0), Vec2::new(0.0, 2.0), true)
+    );
+    assert_eq!(
+        Boundary::Bounce.apply(min, max, pos, vel),
+        (Vec2::new(2.0, 50.0), Vec2::new(3.0, 2.0), true)
+    );
+    // ships already heading back in keep going
+    let (pos, vel) = (Vec2::new(102.0, -1.0), Vec2::new(-3.0, -2.0));
+    assert_eq!(
+        Boundary::Bounce.apply(min, max, pos, vel),
+        (Vec2::new(98.0, 1.0), Vec2::new(-3.0, 2.0), true)
+    );
+    let damage = Boundary::Damage {
+        interval: 1,
+        damage: 1,
+    };
+    assert_eq!(damage.apply(min, max, pos, vel), (pos, vel, true));
+}
+
+#[test]
+fn ships_stay_inside_and_report_contacts() {
+    for boundary in [Boundary::Clamp, Boundary::Bounce] {
+        let bounds = Bounds::new(1000.0, 1000.0).with_boundary(boundary);
+        let mut swarm = swarm_into_the_wall();
+        let mut events = Vec::new();
+        for _ in 0..200 {
+            swarm.movement(&bounds, &mut events);
+            swarm.finalize(&bounds);
+            for (ship, _) in &swarm.ships {
+                assert!(bounds.contains(ship.pos), "{boundary:?}: {}", ship.pos);
+            }
+        }
+        assert!(events.iter().all(|event| matches!(
+            event,
+            EventKind::WallContact { swarm: SwarmId(0), pos, .. } if pos.x < 20.0
+        )));
+        // clamped ships stay pinned to the wall, bouncing ships keep coming back
+        let min_contacts = match boundary {
+            Boundary::Clamp => 8 * 100,
+            _ => 8,
+        };
+        assert!(
+            events.len() > min_contacts,
+            "{boundary:?}: {}",
+            events.len()
+        );
+    }
+}
+
+#[test]
+fn ships_outside_the_walls_take_damage() {
+    let bounds = Bounds::new(1000.0, 1000.0).with_boundary(Boundary::Damage {
+        interval: 10,
+        damage: 3,
+    });
+    let mut sim = Simulation::new(
+        SimulationConfig {
+            init_swarms: 0,
+            respawn_interval: 0,
+            ..Default::default()
+        },
+        bounds,
+    );
+    let outside = sim.spawn_swarm(Vec2::new(-400.0, 500.0), 4);
+    let inside = sim.spawn_swarm(Vec2::new(700.0, 500.0), 4);
+    sim.drain_events();
+
+    for _ in 0..11 {
+        sim.step();
+    }
+    let events: Vec<_> = sim.drain_events().collect();
+    let contacts = |id| {
+        events
+            .iter()
+            .filter(
+                |event| matches!(event.kind, EventKind::WallContact { swarm, .. } if swarm == id),
+            )
+            .count()
+    };
+    assert!(contacts(outside) > 0);
+    assert_eq!(contacts(inside), 0);
+    let wall_kills = events
+        .iter()
+        .filter(|event| matches!(event.kind, EventKind::WallDamage { swarm, health_left: 0, .. } if swarm == outside))
+        .count();
+    assert_eq!(wall_kills, 4);
+    let destroyed = events
+        .iter()
+        .filter(|event| matches!(event.kind, EventKind::ShipDestroyed { swarm, cause: DestroyCause::Walls, .. } if swarm == outside))
+        .count();
+    assert_eq!(destroyed, 4);
+    assert!(sim.swarm(outside).is_none());
+    assert_eq!(sim.swarm(inside).unwrap().num_ships(), 4);
+}
+
+#[test]
+fn scenarios_configure_the_boundary() {
+    let scenario = Scenario::from_toml("[arena]\nboundary = \"bounce\"\n").unwrap();
+    assert_eq!(scenario.build().bounds().boundary, Boundary::Bounce);
+    let scenario =
+        Scenario::from_toml("[arena]\nboundary = { damage = { interval = 30, damage = 2 } }\n")
+            .unwrap();
+    assert_eq!(
+        scenario.bounds().boundary,
+        Boundary::Damage {
+            interval: 30,
+            damage: 2
+        }
+    );
+    assert_eq!(Scenario::default().bounds().boundary, Boundary::Clamp);
+
+    let err =
+        Scenario::from_toml("[arena]\nboundary = { damage = { interval = 0, damage = 2 } }\n")
+            .unwrap_err();
+    assert!(err.to_string().contains("arena.boundary"), "{err}");
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{EmptySim, empty_sim};
+use glam::Vec2;
+use swarm_simulation::brain::{RepulsionBrain, SwarmBrain};
+use swarm_simulation::observation::Observation;
+use swarm_simulation::swarm::{Intent, SwarmConfig, SwarmDecision, SwarmId};
+
+/// Always heads for the same point
+struct GotoBrain(Vec2);
+
+impl SwarmBrain for GotoBrain {
+    fn name(&self) -> &str {
+        "goto"
+    }
+
+    fn decide(&self, _observation: &Observation) -> Option<SwarmDecision> {
+        Some(SwarmDecision {
+            target: self.0,
+            intent: Intent::Move,
+        })
+    }
+}
+
+#[test]
+fn swarms_use_the_default_brain() {
+    let mut sim = empty_sim();
+    let id = sim.spawn_swarm(Vec2::new(100.0, 100.0), 3);
+    assert_eq!(sim.swarm(id).unwrap().brain.name(), "repulsion");
+}
+
+#[test]
+fn brains_can_be_assigned_per_swarm() {
+    // idle swarms stay put without roaming
+    let mut sim = EmptySim {
+        swarm: SwarmConfig {
+            roam_interval: 0,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .build();
+    let a = sim.spawn_swarm(Vec2::new(500.0, 500.0), 3);
+    let b = sim.spawn_swarm(Vec2::new(2500.0, 2500.0), 3);
+    sim.swarm_mut(a).unwrap().brain = Arc::new(GotoBrain(Vec2::new(1000.0, 500.0)));
+
+    for _ in 0..300 {
+        sim.step();
+    }
+    let center = |id| sim.swarm(id).unwrap().center;
+    assert!(center(a).distance(Vec2::new(1000.0, 500.0)) < 20.0);
+    assert!(center(b).distance(Vec2::new(2500.0, 2500.0)) < 20.0);
+}
+
+#[test]
+fn brain_factory_picks_brains_for_new_swarms() {
+    let mut sim = empty_sim();
+    let goto: Arc<dyn SwarmBrain> = Arc::new(GotoBrain(Vec2::new(1500.0, 1500.0)));
+    sim.set_brain_factory(move |id: SwarmId| {
+        if id.0.is_multiple_of(2) {
+            Arc::clone(&goto)
+        } else {
+            Arc::new(RepulsionBrain)
+        }
+    });
+
+    let ids: Vec<SwarmId> = (0..4)
+        .map(|i| sim.spawn_swarm(Vec2::new(200.0 + i as f32 * 100.0, 200.0), 2))
+        .collect();
+    let names: Vec<&str> = ids
+        .iter()
+        .map(|id| sim.swarm(*id).unwrap().brain.name())
+        .collect();
+    assert_eq!(names, ["goto", "repulsion", "goto", "repulsion"]);
+}
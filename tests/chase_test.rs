@@ -0,0 +1,75 @@
+mod common;
+
+use glam::Vec2;
+use swarm_simulation::brain::{RepulsionBrain, SwarmBrain};
+use swarm_simulation::observation::{Contact, Observation};
+use swarm_simulation::swarm::SwarmId;
+
+const CENTER: Vec2 = Vec2::new(1000.0, 1000.0);
+
+/// Observation of a resting swarm of 10 ships that only sees the given prey
+fn observation(contacts: Vec<Contact>) -> Observation {
+    let mut observation = common::observation(CENTER);
+    observation.own.personality.engage_ratio = 0.7;
+    observation.contacts = contacts;
+    observation
+}
+
+fn prey(swarm: u64, center: Vec2, velocity: Vec2, num_ships: u32) -> Contact {
+    Contact {
+        swarm: SwarmId(swarm),
+        center,
+        velocity,
+        num_ships,
+        max_speed: 10.0,
+        distance: center.distance(CENTER),
+    }
+}
+
+fn chase(contacts: Vec<Contact>) -> Option<Vec2> {
+    RepulsionBrain
+        .decide(&observation(contacts))
+        .map(|decision| decision.target)
+}
+
+#[test]
+fn moving_prey_is_intercepted_ahead() {
+    let target = chase(vec![prey(
+        1,
+        Vec2::new(1300.0, 1000.0),
+        Vec2::new(0.0, 4.0),
+        3,
+    )])
+    .unwrap();
+    assert!(target.y > 1100.0, "{target}");
+    assert_eq!(target.x, 1300.0);
+
+    // resting prey is targeted directly
+    let target = chase(vec![prey(1, Vec2::new(1300.0, 1000.0), Vec2::ZERO, 3)]).unwrap();
+    assert_eq!(target, Vec2::new(1300.0, 1000.0));
+}
+
+#[test]
+fn prey_that_cant_be_caught_is_ignored() {
+    let as_fast = prey(1, Vec2::new(1300.0, 1000.0), Vec2::new(10.0, 0.0), 3);
+    assert_eq!(chase(vec![as_fast]), None);
+
+    // slower, but too far ahead to catch up within the horizon
+    let slightly_slower = prey(1, Vec2::new(1450.0, 1000.0), Vec2::new(9.0, 0.0), 3);
+    assert_eq!(chase(vec![slightly_slower]), None);
+
+    // the same prey coming towards us is easy to catch
+    let approaching = prey(1, Vec2::new(1450.0, 1000.0), Vec2::new(-9.0, 0.0), 3);
+    assert!(chase(vec![approaching]).is_some());
+}
+
+#[test]
+fn prey_is_scored_by_size_and_catchability() {
+    let fleeing = prey(1, Vec2::new(1200.0, 1000.0), Vec2::new(8.0, 0.0), 3);
+    let resting = prey(2, Vec2::new(1000.0, 1400.0), Vec2::ZERO, 3);
+    assert_eq!(chase(vec![fleeing, resting.clone()]), Some(resting.center));
+
+    let big = prey(1, Vec2::new(1300.0, 1000.0), Vec2::ZERO, 6);
+    let small = prey(2, Vec2::new(1000.0, 1300.0), Vec2::ZERO, 2);
+    assert_eq!(chase(vec![big, small.clone()]), Some(small.center));
+}
@@ -0,0 +1,96 @@
+#![allow(dead_code)] // every test file uses only some of the fixtures
+
+use glam::Vec2;
+use swarm_simulation::beacon::BeaconConfig;
+use swarm_simulation::observation::{Observation, OwnSwarm};
+use swarm_simulation::personality::PersonalityConfig;
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::swarm::{SwarmConfig, SwarmId};
+
+/// Observation of a resting swarm of 10 ships at `center` with a midpoint
+/// personality that sees nothing. Tests override the fields they check.
+pub fn observation(center: Vec2) -> Observation {
+    Observation {
+        tick: 0,
+        own: OwnSwarm {
+            id: SwarmId(0),
+            team: None,
+            center,
+            velocity: Vec2::ZERO,
+            target_pos: center,
+            direction: 0.0,
+            num_ships: 10,
+            max_ships: 30,
+            occupancy: 0.33,
+            vision_range: 500.0,
+            max_speed: 10.0,
+            max_accel: 0.3,
+            kite_distance: 275.0,
+            memory_ticks: 180,
+            roam_interval: 0,
+            coordination: true,
+            personality: PersonalityConfig::default().midpoint(),
+        },
+        bounds: Bounds::new(4000.0, 4000.0),
+        contacts: Vec::new(),
+        allies: Vec::new(),
+        ships: Vec::new(),
+        beacons: Vec::new(),
+        remembered: Vec::new(),
+        remembered_beacons: Vec::new(),
+        explored: Default::default(),
+    }
+}
+
+/// Simulation with 10 random swarms and no respawning
+pub fn seeded_sim(seed: u64) -> Simulation {
+    let mut sim = Simulation::new(
+        SimulationConfig {
+            seed,
+            max_swarms: 10,
+            init_swarms: 10,
+            respawn_interval: 0,
+            ..Default::default()
+        },
+        Bounds::new(1500.0, 1500.0),
+    );
+    sim.spawn_initial_swarms();
+    sim
+}
+
+/// Settings of a simulation without swarms, respawns or random beacons in a
+/// 3000x3000 arena. Tests override what they need and `build` it.
+pub struct EmptySim {
+    pub config: SimulationConfig,
+    pub bounds: Bounds,
+    pub swarm: SwarmConfig,
+}
+
+impl Default for EmptySim {
+    fn default() -> Self {
+        EmptySim {
+            config: SimulationConfig {
+                init_swarms: 0,
+                respawn_interval: 0,
+                beacons: BeaconConfig {
+                    spawn_interval: 0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            bounds: Bounds::new(3000.0, 3000.0),
+            swarm: SwarmConfig::default(),
+        }
+    }
+}
+
+impl EmptySim {
+    pub fn build(self) -> Simulation {
+        Simulation::with_configs(self.config, self.bounds, Default::default(), self.swarm)
+    }
+}
+
+/// `EmptySim` with the default settings
+pub fn empty_sim() -> Simulation {
+    EmptySim::default().build()
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use glam::Vec2;
+use swarm_simulation::event::{DestroyCause, Event, EventKind};
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+
+#[test]
+fn spawning_emits_event() {
+    let mut sim = Simulation::new(SimulationConfig::default(), Bounds::new(500.0, 500.0));
+    let id = sim.spawn_swarm(Vec2::new(100.0, 100.0), 4);
+
+    let events: Vec<Event> = sim.drain_events().collect();
+    assert_eq!(
+        events,
+        vec![Event {
+            tick: 0,
+            kind: EventKind::SwarmSpawned {
+                swarm: id,
+                pos: Vec2::new(100.0, 100.0),
+                num_ships: 4,
+                team: None,
+                personality: sim.swarm(id).unwrap().personality,
+            },
+        }]
+    );
+    assert_eq!(sim.drain_events().count(), 0);
+}
+
+#[test]
+fn combat_events_match_stats() {
+    let mut sim = common::seeded_sim(8);
+    sim.drain_events();
+
+    let mut events = Vec::new();
+    for _ in 0..3000 {
+        sim.step();
+        events.extend(sim.drain_events());
+    }
+
+    let count = |f: fn(&EventKind) -> bool| events.iter().filter(|e| f(&e.kind)).count() as u32;
+    let shots = count(|k| matches!(k, EventKind::ShotFired { .. }));
+    let destroyed = count(|k| {
+        matches!(
+            k,
+            EventKind::ShipDestroyed {
+                cause: DestroyCause::Shot { .. },
+                ..
+            }
+        )
+    });
+    let eliminated = count(|k| matches!(k, EventKind::SwarmEliminated { .. }));
+    let damage = count(|k| matches!(k, EventKind::DamageApplied { .. }));
+
+    let alive = sim.swarms().iter().map(|s| &s.stats);
+    let all_stats: Vec<_> = alive
+        .chain(sim.eliminated().iter().map(|(_, stats)| stats))
+        .collect();
+    assert_eq!(shots, all_stats.iter().map(|s| s.shots_fired).sum::<u32>());
+    assert_eq!(destroyed, all_stats.iter().map(|s| s.kills).sum::<u32>());
+    assert_eq!(eliminated, sim.eliminated().len() as u32);
+    assert!(damage <= shots);
+    assert!(destroyed > 0);
+
+    // every shot is preceded by a lock of the same ship on the same target
+    for (idx, event) in events.iter().enumerate() {
+        if let EventKind::ShotFired {
+            swarm,
+            shooter,
+            target,
+        } = event.kind
+        {
+            let lock = EventKind::LockAcquired {
+                swarm,
+                ship: shooter,
+                target,
+            };
+            let locked = events[..idx].iter().any(|e| e.kind == lock);
+            assert!(locked, "shot at tick {} without a lock", event.tick);
+        }
+    }
+}
+
+#[test]
+fn subscribers_see_the_same_events() {
+    let mut sim = common::seeded_sim(11);
+    let seen: Arc<Mutex<Vec<Event>>> = Arc::default();
+    let seen_by_callback = Arc::clone(&seen);
+    sim.subscribe(move |event| seen_by_callback.lock().unwrap().push(event.clone()));
+    sim.drain_events();
+
+    let mut drained = Vec::new();
+    for _ in 0..1000 {
+        sim.step();
+        drained.extend(sim.drain_events());
+    }
+
+    assert!(!drained.is_empty());
+    assert_eq!(*seen.lock().unwrap(), drained);
+}
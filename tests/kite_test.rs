@@ -0,0 +1,131 @@
+mod common;
+
+use std::sync::Arc;
+
+use glam::Vec2;
+use swarm_simulation::brain::{RepulsionBrain, SwarmBrain};
+use swarm_simulation::observation::{Contact, Observation};
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::swarm::{GrowthCurve, Intent, SwarmConfig, SwarmDecision, SwarmId};
+
+const CENTER: Vec2 = Vec2::new(1000.0, 1000.0);
+
+/// Observation of a small swarm that only sees the given enemies
+fn observation(contacts: Vec<Contact>, max_speed: f32, kite_distance: f32) -> Observation {
+    let mut observation = common::observation(CENTER);
+    observation.own.velocity = Vec2::new(0.0, 5.0);
+    observation.own.num_ships = 5;
+    observation.own.occupancy = 0.14;
+    observation.own.max_speed = max_speed;
+    observation.own.kite_distance = kite_distance;
+    observation.own.personality.risk_tolerance = 0.0;
+    observation.contacts = contacts;
+    observation
+}
+
+fn threat(swarm: u64, center: Vec2) -> Contact {
+    Contact {
+        swarm: SwarmId(swarm),
+        center,
+        velocity: Vec2::ZERO,
+        num_ships: 30,
+        max_speed: 7.0,
+        distance: center.distance(CENTER),
+    }
+}
+
+#[test]
+fn lone_slower_threats_are_kited() {
+    let big = threat(1, Vec2::new(1400.0, 1000.0));
+    let decision = RepulsionBrain
+        .decide(&observation(vec![big.clone()], 9.0, 275.0))
+        .unwrap();
+    assert_eq!(
+        decision.intent,
+        Intent::Standoff {
+            enemy: big.swarm,
+            distance: 275.0
+        }
+    );
+    assert!((decision.target.distance(big.center) - 275.0).abs() < 0.01);
+    // keeps circling the way we're already moving instead of heading straight for the threat
+    assert!(decision.target.y > CENTER.y);
+}
+
+#[test]
+fn threats_are_fled_from_when_kiting_is_not_possible() {
+    let big = threat(1, Vec2::new(1400.0, 1000.0));
+    let flee = |contacts: Vec<Contact>, max_speed, kite_distance| {
+        RepulsionBrain
+            .decide(&observation(contacts, max_speed, kite_distance))
+            .unwrap()
+            .intent
+    };
+    // not fast enough
+    assert_eq!(flee(vec![big.clone()], 7.5, 275.0), Intent::Flee);
+    // kiting disabled
+    assert_eq!(flee(vec![big.clone()], 9.0, 0.0), Intent::Flee);
+    // more than one threat
+    let other = threat(2, Vec2::new(1000.0, 1400.0));
+    assert_eq!(flee(vec![big, other], 9.0, 275.0), Intent::Flee);
+}
+
+/// Stays where it is
+struct HoldBrain(Vec2);
+
+impl SwarmBrain for HoldBrain {
+    fn name(&self) -> &str {
+        "hold"
+    }
+
+    fn decide(&self, _observation: &Observation) -> Option<SwarmDecision> {
+        Some(SwarmDecision {
+            target: self.0,
+            intent: Intent::Move,
+        })
+    }
+}
+
+#[test]
+fn kiting_swarms_hold_distance_and_keep_moving() {
+    let mut sim = Simulation::with_configs(
+        SimulationConfig {
+            init_swarms: 0,
+            respawn_interval: 0,
+            ..Default::default()
+        },
+        Bounds::new(3000.0, 3000.0),
+        Default::default(),
+        SwarmConfig {
+            kite_range: 2.0,
+            // the big swarm has to be slower than the kiter
+            speed_penalty: GrowthCurve {
+                at_max: 0.7,
+                exponent: 1.0,
+            },
+            ..Default::default()
+        },
+    );
+    let big_pos = Vec2::new(1500.0, 1500.0);
+    let big = sim.spawn_swarm(big_pos, 30);
+    sim.swarm_mut(big).unwrap().brain = Arc::new(HoldBrain(big_pos));
+    let kiter = sim.spawn_swarm(Vec2::new(1100.0, 1500.0), 5);
+    sim.swarm_mut(kiter).unwrap().personality.risk_tolerance = 0.0;
+
+    for _ in 0..200 {
+        sim.step();
+    }
+    let mut travelled = 0.0;
+    for _ in 0..600 {
+        let before = sim.swarm(kiter).unwrap().center;
+        sim.step();
+        let swarm = sim.swarm(kiter).unwrap();
+        travelled += swarm.center.distance(before);
+
+        let distance = swarm.center.distance(big_pos);
+        assert!((400.0..600.0).contains(&distance), "{distance}");
+        assert!(matches!(swarm.intent, Intent::Standoff { enemy, .. } if enemy == big));
+    }
+    assert!(travelled / 600.0 > 5.0, "{travelled}");
+    assert_eq!(sim.swarm(kiter).unwrap().num_ships(), 5);
+}
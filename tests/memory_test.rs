@@ -0,0 +1,160 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::EmptySim;
+use glam::Vec2;
+use swarm_simulation::brain::{RepulsionBrain, SwarmBrain};
+use swarm_simulation::observation::{Contact, Observation, RememberedContact};
+use swarm_simulation::simulation::Simulation;
+use swarm_simulation::swarm::{Intent, SwarmConfig, SwarmDecision, SwarmId};
+
+/// Always heads for the same point
+struct GotoBrain(Vec2);
+
+impl SwarmBrain for GotoBrain {
+    fn name(&self) -> &str {
+        "goto"
+    }
+
+    fn decide(&self, _observation: &Observation) -> Option<SwarmDecision> {
+        Some(SwarmDecision {
+            target: self.0,
+            intent: Intent::Move,
+        })
+    }
+}
+
+fn sim_with_memory(memory_ticks: u32) -> Simulation {
+    EmptySim {
+        swarm: SwarmConfig {
+            vision_range: 500.0,
+            memory_ticks,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .build()
+}
+
+/// Spawn a resting observer and a swarm that leaves its vision range,
+/// step until the other swarm is out of sight
+fn lose_sight(sim: &mut Simulation) -> (SwarmId, SwarmId) {
+    let me = sim.spawn_swarm(Vec2::new(1000.0, 1000.0), 5);
+    let other = sim.spawn_swarm(Vec2::new(1300.0, 1000.0), 5);
+    sim.swarm_mut(me).unwrap().brain = Arc::new(GotoBrain(Vec2::new(1000.0, 1000.0)));
+    sim.swarm_mut(other).unwrap().brain = Arc::new(GotoBrain(Vec2::new(2900.0, 1000.0)));
+
+    while !sim.observe(me).unwrap().contacts.is_empty() {
+        sim.step();
+    }
+    (me, other)
+}
+
+#[test]
+fn lost_contacts_are_remembered_until_they_expire() {
+    let mut sim = sim_with_memory(60);
+    let (me, other) = lose_sight(&mut sim);
+
+    let observation = sim.observe(me).unwrap();
+    assert_eq!(observation.remembered.len(), 1);
+    let memory = &observation.remembered[0];
+    assert_eq!(memory.swarm, other);
+    assert_eq!(memory.num_ships, 5);
+    assert_eq!(memory.last_seen, sim.tick() - 1);
+    assert!(observation.estimated_pos(memory).x > memory.pos.x);
+    assert!(observation.confidence(memory) < 1.0);
+
+    let last_seen = memory.last_seen;
+    while sim.tick() - last_seen < 60 {
+        assert_eq!(sim.observe(me).unwrap().remembered.len(), 1);
+        sim.step();
+    }
+    assert!(sim.observe(me).unwrap().remembered.is_empty());
+}
+
+#[test]
+fn memories_are_dropped_when_the_estimated_position_is_seen_empty() {
+    let mut sim = sim_with_memory(10_000);
+    let me = sim.spawn_swarm(Vec2::new(1000.0, 1000.0), 5);
+    sim.swarm_mut(me).unwrap().brain = Arc::new(GotoBrain(Vec2::new(1000.0, 1000.0)));
+    // a swarm heading into our vision range, and one heading away
+    let approaching = RememberedContact {
+        velocity: Vec2::new(-5.0, 0.0),
+        ..remembered(1, Vec2::new(1700.0, 1000.0), 5)
+    };
+    let leaving = RememberedContact {
+        velocity: Vec2::new(0.0, 5.0),
+        ..remembered(2, Vec2::new(1000.0, 1700.0), 5)
+    };
+    sim.swarm_mut(me).unwrap().memory = vec![approaching, leaving];
+
+    let remembered_ids = |sim: &Simulation| -> Vec<u64> {
+        let observation = sim.observe(me).unwrap();
+        observation.remembered.iter().map(|m| m.swarm.0).collect()
+    };
+    assert_eq!(remembered_ids(&sim), [1, 2]);
+    // the approaching swarm should be within 500 after 40 ticks, but isn't there
+    for _ in 0..50 {
+        sim.step();
+    }
+    assert_eq!(remembered_ids(&sim), [2]);
+}
+
+fn observation(contacts: Vec<Contact>, remembered: Vec<RememberedContact>) -> Observation {
+    let mut observation = common::observation(Vec2::new(1000.0, 1000.0));
+    observation.tick = 10;
+    observation.contacts = contacts;
+    observation.remembered = remembered;
+    observation
+}
+
+fn contact(swarm: u64, center: Vec2, num_ships: u32) -> Contact {
+    Contact {
+        swarm: SwarmId(swarm),
+        center,
+        velocity: Vec2::ZERO,
+        num_ships,
+        max_speed: 10.0,
+        distance: center.distance(Vec2::new(1000.0, 1000.0)),
+    }
+}
+
+fn remembered(swarm: u64, pos: Vec2, num_ships: u32) -> RememberedContact {
+    RememberedContact {
+        swarm: SwarmId(swarm),
+        pos,
+        velocity: Vec2::ZERO,
+        num_ships,
+        last_seen: 0,
+    }
+}
+
+#[test]
+fn prey_next_to_a_remembered_threat_is_not_chased() {
+    let prey = contact(1, Vec2::new(1300.0, 1000.0), 3);
+    let threat = remembered(2, Vec2::new(1600.0, 1000.0), 30);
+
+    let unguarded = RepulsionBrain.decide(&observation(vec![prey.clone()], Vec::new()));
+    assert_eq!(unguarded.unwrap().target, prey.center);
+
+    let guarded = RepulsionBrain.decide(&observation(vec![prey], vec![threat]));
+    assert!(guarded.is_none());
+}
+
+#[test]
+fn fleeing_swarms_avoid_remembered_threats() {
+    let threat = contact(1, Vec2::new(1000.0, 1300.0), 30);
+    let lost_threat = remembered(2, Vec2::new(600.0, 800.0), 30);
+
+    let without_memory = RepulsionBrain
+        .decide(&observation(vec![threat.clone()], Vec::new()))
+        .unwrap();
+    let with_memory = RepulsionBrain
+        .decide(&observation(vec![threat], vec![lost_threat.clone()]))
+        .unwrap();
+    assert_eq!(with_memory.intent, Intent::Flee);
+
+    let away = |decision: &SwarmDecision| decision.target.distance(lost_threat.pos);
+    assert!(away(&with_memory) > away(&without_memory));
+}
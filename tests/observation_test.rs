@@ -0,0 +1,72 @@
+mod common;
+
+use common::EmptySim;
+use glam::Vec2;
+use swarm_simulation::swarm::SwarmConfig;
+
+#[test]
+fn observations_only_contain_what_is_in_vision() {
+    let mut sim = EmptySim {
+        swarm: SwarmConfig {
+            vision_range: 500.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .build();
+    let me = sim.spawn_swarm(Vec2::new(1000.0, 1000.0), 5);
+    let near = sim.spawn_swarm(Vec2::new(1300.0, 1000.0), 3);
+    let far = sim.spawn_swarm(Vec2::new(2000.0, 1000.0), 20);
+    let near_beacon = sim.spawn_beacon(Vec2::new(1000.0, 1400.0), 2);
+    sim.spawn_beacon(Vec2::new(1000.0, 2000.0), 2);
+
+    let observation = sim.observe(me).unwrap();
+    assert_eq!(observation.own.id, me);
+    assert_eq!(observation.own.num_ships, 5);
+
+    let contacts: Vec<_> = observation.contacts.iter().map(|c| c.swarm).collect();
+    assert_eq!(contacts, [near]);
+    assert_eq!(observation.contacts[0].num_ships, 3);
+    assert_eq!(observation.contacts[0].distance, 300.0);
+
+    assert_eq!(observation.ships.len(), 3);
+    assert!(observation.ships.iter().all(|ship| ship.swarm == near));
+    assert!(observation.ships.iter().all(|ship| ship.swarm != far));
+
+    let beacons: Vec<_> = observation.beacons.iter().map(|b| b.id).collect();
+    assert_eq!(beacons, [near_beacon]);
+
+    assert!(sim.observe(far).unwrap().contacts.is_empty());
+}
+
+#[test]
+fn observation_noise_is_bounded_and_reproducible() {
+    let mut sim = EmptySim {
+        swarm: SwarmConfig {
+            observation_noise: 40.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .build();
+    let me = sim.spawn_swarm(Vec2::new(1000.0, 1000.0), 5);
+    let other = sim.spawn_swarm(Vec2::new(1250.0, 1000.0), 5);
+
+    let first = sim.observe(me).unwrap();
+    let true_center = sim.swarm(other).unwrap().center;
+    let error = first.contacts[0].center.distance(true_center);
+    assert!(error > 0.0);
+    // 250 of 500 vision range -> at most half the noise on each axis
+    assert!(error <= 20.0 * 2f32.sqrt(), "{error}");
+
+    let again = sim.observe(me).unwrap();
+    assert_eq!(first.contacts[0].center, again.contacts[0].center);
+
+    sim.step();
+    let later = sim.observe(me).unwrap();
+    let true_center = sim.swarm(other).unwrap().center;
+    assert_ne!(
+        first.contacts[0].center - true_center,
+        later.contacts[0].center - true_center
+    );
+}
@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use glam::Vec2;
+use swarm_simulation::event::EventKind;
+use swarm_simulation::obstacle::{self, Obstacle};
+use swarm_simulation::scenario::Scenario;
+use swarm_simulation::ship::{Ship, ShipConfig, ShipId, ShipIdAllocator};
+use swarm_simulation::simulation::Bounds;
+use swarm_simulation::swarm::{Swarm, SwarmConfig, SwarmId};
+
+fn square(min: f32, max: f32) -> Vec<Vec2> {
+    vec![
+        Vec2::new(min, min),
+        Vec2::new(max, min),
+        Vec2::new(max, max),
+        Vec2::new(min, max),
+    ]
+}
+
+#[test]
+fn obstacles_know_their_solid_part() {
+    let circle = Obstacle::Circle {
+        center: Vec2::new(100.0, 100.0),
+        radius: 50.0,
+    };
+    assert!(circle.contains(Vec2::new(120.0, 100.0)));
+    assert!(!circle.contains(Vec2::new(160.0, 100.0)));
+    assert_eq!(circle.raycast(Vec2::new(0.0, 100.0), Vec2::X), Some(50.0));
+    assert_eq!(circle.raycast(Vec2::new(0.0, 100.0), -Vec2::X), None);
+
+    let asteroid = Obstacle::Polygon {
+        points: square(0.0, 100.0),
+    };
+    assert!(asteroid.contains(Vec2::new(50.0, 50.0)));
+    assert!(!asteroid.contains(Vec2::new(150.0, 50.0)));
+    assert_eq!(
+        asteroid.raycast(Vec2::new(150.0, 50.0), -Vec2::X),
+        Some(50.0)
+    );
+
+    // L-shaped arena: the top right quarter is solid
+    let walls = Obstacle::Walls {
+        points: vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 50.0),
+            Vec2::new(50.0, 50.0),
+            Vec2::new(50.0, 100.0),
+            Vec2::new(0.0, 100.0),
+        ],
+    };
+    assert!(!walls.contains(Vec2::new(25.0, 75.0)));
+    assert!(walls.contains(Vec2::new(75.0, 75.0)));
+    assert!(walls.contains(Vec2::new(-10.0, 25.0)));
+    assert_eq!(walls.raycast(Vec2::new(25.0, 75.0), Vec2::X), Some(25.0));
+}
+
+#[test]
+fn obstacles_block_line_of_sight() {
+    let obstacles = [Obstacle::Circle {
+        center: Vec2::new(100.0, 100.0),
+        radius: 50.0,
+    }];
+    assert!(!obstacle::line_of_sight(
+        &obstacles,
+        Vec2::new(0.0, 100.0),
+        Vec2::new(200.0, 100.0)
+    ));
+    assert!(obstacle::line_of_sight(
+        &obstacles,
+        Vec2::new(0.0, 0.0),
+        Vec2::new(200.0, 0.0)
+    ));
+}
+
+#[test]
+fn ships_slide_along_obstacles() {
+    let obstacles = [Obstacle::Polygon {
+        points: square(100.0, 200.0),
+    }];
+    // target is behind the obstacle
+    let mut ship = Ship::spawn(
+        ShipId(0),
+        Vec2::new(0.0, 140.0),
+        Arc::new(ShipConfig::default()),
+    );
+    ship.set_target(Vec2::new(300.0, 160.0));
+
+    for _ in 0..300 {
+        ship.movement(1.0, 1.0);
+        ship.collide(&obstacles);
+        assert!(
+            obstacle::is_free(&obstacles, ship.pos),
+            "inside at {}",
+            ship.pos
+        );
+    }
+    // stuck at the wall, but slid along it as close to the target as it gets
+    assert!(ship.pos.x < 100.0);
+    assert!((ship.pos.y - 160.0).abs() < 1.0, "ended at {}", ship.pos);
+}
+
+fn lone_ship_swarm(
+    id: u64,
+    pos: Vec2,
+    ship_ids: &mut ShipIdAllocator,
+    line_of_sight: bool,
+) -> Swarm {
+    let config = SwarmConfig::default();
+    Swarm::spawn(
+        SwarmId(id),
+        pos,
+        1,
+        config.personality.midpoint(),
+        ship_ids,
+        Arc::new(config),
+        Arc::new(ShipConfig {
+            line_of_sight,
+            ..Default::default()
+        }),
+    )
+}
+
+#[test]
+fn obstacles_optionally_block_locks() {
+    let bounds = Bounds::new(1000.0, 1000.0).with_obstacles(vec![Obstacle::Circle {
+        center: Vec2::new(500.0, 500.0),
+        radius: 40.0,
+    }]);
+    for line_of_sight in [true, false] {
+        let mut ship_ids = ShipIdAllocator::default();
+        let mut swarm = lone_ship_swarm(0, Vec2::new(400.0, 500.0), &mut ship_ids, line_of_sight);
+        let enemy = lone_ship_swarm(1, Vec2::new(600.0, 500.0), &mut ship_ids, line_of_sight);
+        let enemies: Vec<&Ship> = enemy.ships.iter().map(|(ship, _)| ship).collect();
+
+        let mut events = Vec::new();
+        swarm.fight(&enemies, 1.0, &bounds, &mut events);
+        let locked = events
+            .iter()
+            .any(|event| matches!(event, EventKind::LockAcquired { .. }));
+        assert_eq!(locked, !line_of_sight);
+    }
+}
+
+#[test]
+fn scenarios_define_obstacles() {
+    let scenario = Scenario::load(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/scenarios/asteroids.toml"
+    ))
+    .unwrap();
+    let mut sim = scenario.build();
+    assert_eq!(sim.bounds().obstacles.len(), 5);
+
+    for _ in 0..600 {
+        sim.step();
+    }
+    for swarm in sim.swarms() {
+        for (ship, _) in &swarm.ships {
+            assert!(
+                sim.bounds().is_free(ship.pos),
+                "ship inside at {}",
+                ship.pos
+            );
+        }
+    }
+
+    let source = "[arena]\nobstacles = [{ circle = { center = [100.0, 100.0], radius = 50.0 } }]\n\
+                  [[swarms]]\npos = [110.0, 90.0]\nships = 3\n";
+    let err = Scenario::from_toml(source).unwrap_err();
+    assert!(err.to_string().contains("inside an obstacle"), "{err}");
+}
@@ -0,0 +1,60 @@
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+
+fn assert_thread_safe<T: Send + Sync>() {}
+
+#[test]
+fn simulations_can_move_between_threads() {
+    assert_thread_safe::<Simulation>();
+
+    let mut sim = Simulation::new(SimulationConfig::default(), Bounds::new(2000.0, 2000.0));
+    sim.spawn_initial_swarms();
+    let sim = std::thread::spawn(move || {
+        for _ in 0..100 {
+            sim.step();
+        }
+        sim
+    })
+    .join()
+    .unwrap();
+    assert_eq!(sim.tick(), 100);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_steps_match_serial_steps() {
+    use swarm_simulation::event::EventKind;
+    use swarm_simulation::replay::state_hash;
+
+    let sim = |parallel| {
+        let mut sim = Simulation::new(
+            SimulationConfig {
+                seed: 5,
+                init_swarms: 20,
+                max_swarms: 20,
+                teams: 2,
+                ..Default::default()
+            },
+            Bounds::new(2000.0, 2000.0),
+        );
+        sim.set_parallel(parallel);
+        sim.spawn_initial_swarms();
+        sim
+    };
+    let (mut serial, mut parallel) = (sim(false), sim(true));
+    let mut shots = 0;
+    for _ in 0..10 {
+        for _ in 0..100 {
+            serial.step();
+            parallel.step();
+        }
+        assert_eq!(state_hash(&serial).unwrap(), state_hash(&parallel).unwrap());
+        let events: Vec<_> = serial.drain_events().collect();
+        shots += events
+            .iter()
+            .filter(|event| matches!(event.kind, EventKind::ShotFired { .. }))
+            .count();
+        assert!(events.into_iter().eq(parallel.drain_events()));
+    }
+    // the swarms got to fight, not just to move
+    assert!(shots > 0);
+}
@@ -0,0 +1,103 @@
+use glam::Vec2;
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use swarm_simulation::personality::{ParamRange, PersonalityConfig};
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::swarm::{Intent, SwarmConfig};
+
+#[test]
+fn personalities_are_sampled_within_ranges() {
+    let config = PersonalityConfig {
+        aggression: ParamRange::new(0.25, 0.25),
+        ..Default::default()
+    };
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let personalities: Vec<_> = (0..50).map(|_| config.sample(&mut rng)).collect();
+
+    for personality in &personalities {
+        assert_eq!(personality.aggression, 0.25);
+        assert!((0.5..=0.8).contains(&personality.engage_ratio));
+        assert!((300.0..=500.0).contains(&personality.flee_distance));
+    }
+    assert_ne!(personalities[0], personalities[1]);
+}
+
+#[test]
+fn spawned_swarms_get_seeded_personalities() {
+    let sim = |seed| {
+        let mut sim = Simulation::new(
+            SimulationConfig {
+                seed,
+                init_swarms: 5,
+                ..Default::default()
+            },
+            Bounds::new(1500.0, 1500.0),
+        );
+        sim.spawn_initial_swarms();
+        sim.swarms()
+            .iter()
+            .map(|s| s.personality)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(sim(3), sim(3));
+    assert_ne!(sim(3), sim(4));
+}
+
+#[test]
+fn timid_swarms_flee_where_bold_swarms_engage() {
+    let mut sim = Simulation::with_configs(
+        SimulationConfig {
+            init_swarms: 0,
+            respawn_interval: 0,
+            ..Default::default()
+        },
+        Bounds::new(2000.0, 2000.0),
+        Default::default(),
+        SwarmConfig::default(),
+    );
+    let timid = sim.spawn_swarm(Vec2::new(800.0, 1000.0), 10);
+    let bold = sim.spawn_swarm(Vec2::new(1100.0, 1000.0), 10);
+
+    let swarm = sim.swarm_mut(timid).unwrap();
+    swarm.personality.engage_ratio = 0.3;
+    swarm.personality.flee_ratio = 0.5;
+    swarm.personality.risk_tolerance = 0.0;
+    let swarm = sim.swarm_mut(bold).unwrap();
+    swarm.personality.engage_ratio = 1.2;
+    swarm.personality.flee_ratio = 2.0;
+
+    let timid = sim.swarm(timid).unwrap();
+    let bold = sim.swarm(bold).unwrap();
+
+    let decision = timid.decide(&sim).unwrap();
+    assert_eq!(decision.intent, Intent::Flee);
+    assert!(decision.target.x < timid.center.x);
+
+    let decision = bold.decide(&sim).unwrap();
+    assert_eq!(decision.intent, Intent::Move);
+    assert_eq!(decision.target, timid.center);
+}
+
+#[test]
+fn evenly_matched_swarms_keep_hover_distance() {
+    let mut sim = Simulation::new(
+        SimulationConfig {
+            init_swarms: 0,
+            respawn_interval: 0,
+            ..Default::default()
+        },
+        Bounds::new(2000.0, 2000.0),
+    );
+    let a = sim.spawn_swarm(Vec2::new(900.0, 1000.0), 10);
+    let b = sim.spawn_swarm(Vec2::new(1100.0, 1000.0), 10);
+    for id in [a, b] {
+        let swarm = sim.swarm_mut(id).unwrap();
+        swarm.personality.engage_ratio = 0.5;
+        swarm.personality.flee_ratio = 1.5;
+        swarm.personality.hover_distance = 400.0;
+    }
+
+    let decision = sim.swarm(a).unwrap().decide(&sim).unwrap();
+    assert_eq!(decision.intent, Intent::Move);
+    assert!(decision.target.distance(Vec2::new(700.0, 1000.0)) < 1.0);
+}
@@ -0,0 +1,87 @@
+use swarm_simulation::replay::{Replay, ReplayPlayer, ReplayRecorder, state_hash};
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+
+fn recorded_match(seed: u64, ticks: u64) -> (Replay, Simulation) {
+    let mut sim = Simulation::new(
+        SimulationConfig {
+            seed,
+            max_swarms: 10,
+            init_swarms: 6,
+            respawn_interval: 200,
+            ..Default::default()
+        },
+        Bounds::new(1500.0, 1500.0),
+    );
+    sim.spawn_initial_swarms();
+    for _ in 0..50 {
+        sim.step();
+    }
+
+    let recorder = ReplayRecorder::start(&sim);
+    for _ in 0..ticks {
+        sim.step();
+    }
+    (recorder.replay(&sim).unwrap(), sim)
+}
+
+#[test]
+fn playback_reproduces_recording() {
+    let (replay, sim) = recorded_match(2, 1200);
+    assert_eq!(replay.start_tick(), 50);
+    assert_eq!(replay.end_tick(), 1250);
+
+    let replay = Replay::from_bytes(&replay.to_bytes().unwrap()).unwrap();
+    let mut player = ReplayPlayer::new(replay).unwrap();
+    assert_eq!(player.verify(), None);
+
+    while player.step() {}
+    assert_eq!(player.tick(), sim.tick());
+    assert_eq!(player.verify(), Some(true));
+    assert_eq!(state_hash(player.sim()).unwrap(), state_hash(&sim).unwrap());
+}
+
+#[test]
+fn seeking_matches_straight_playback() {
+    let (replay, _) = recorded_match(6, 2000);
+    let mut reference = ReplayPlayer::new(replay.clone()).unwrap();
+    let mut hashes = Vec::new();
+    loop {
+        hashes.push(state_hash(reference.sim()).unwrap());
+        if !reference.step() {
+            break;
+        }
+    }
+
+    let mut player = ReplayPlayer::new(replay.clone()).unwrap();
+    for tick in [1700, 320, 321, 1999, 50, 900, 899, 5000, 0] {
+        player.seek(tick).unwrap();
+        let expected = tick.clamp(replay.start_tick(), replay.end_tick());
+        assert_eq!(player.tick(), expected);
+        let idx = (expected - replay.start_tick()) as usize;
+        assert_eq!(
+            state_hash(player.sim()).unwrap(),
+            hashes[idx],
+            "seek to {tick}"
+        );
+    }
+}
+
+#[test]
+fn diverging_playback_is_detected() {
+    let (mut replay, _) = recorded_match(3, 300);
+    replay.final_state_hash ^= 1;
+
+    let mut player = ReplayPlayer::new(replay).unwrap();
+    player.seek(u64::MAX).unwrap();
+    assert_eq!(player.verify(), Some(false));
+}
+
+#[test]
+fn replays_are_compact() {
+    let (replay, sim) = recorded_match(4, 3000);
+    let replay_size = replay.to_bytes().unwrap().len();
+    let snapshot_size = sim.snapshot().to_bytes().unwrap().len();
+
+    // a replay costs about one snapshot, no matter how many ticks it covers
+    assert!(replay_size < snapshot_size * 2, "{replay_size} bytes");
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{EmptySim, empty_sim};
+use glam::Vec2;
+use swarm_simulation::brain::{RepulsionBrain, SwarmBrain};
+use swarm_simulation::exploration::ExplorationMap;
+use swarm_simulation::observation::Observation;
+use swarm_simulation::simulation::{Bounds, Simulation};
+use swarm_simulation::swarm::{Intent, SwarmConfig, SwarmDecision};
+
+/// Always heads for the same point
+struct GotoBrain(Vec2);
+
+impl SwarmBrain for GotoBrain {
+    fn name(&self) -> &str {
+        "goto"
+    }
+
+    fn decide(&self, _observation: &Observation) -> Option<SwarmDecision> {
+        Some(SwarmDecision {
+            target: self.0,
+            intent: Intent::Move,
+        })
+    }
+}
+
+const CENTER: Vec2 = Vec2::new(500.0, 500.0);
+
+/// Observation of a swarm resting in the corner of an empty arena
+fn observation(tick: u64, explored: ExplorationMap) -> Observation {
+    let mut observation = common::observation(CENTER);
+    observation.tick = tick;
+    observation.own.num_ships = 30;
+    observation.own.occupancy = 1.0;
+    observation.own.roam_interval = 300;
+    observation.bounds = Bounds::new(2000.0, 2000.0);
+    observation.explored = Arc::new(explored);
+    observation
+}
+
+#[test]
+fn exploration_map_tracks_seen_cells() {
+    let mut map = ExplorationMap::new(&Bounds::new(1000.0, 1000.0), 250.0);
+    assert_eq!(map.cells().count(), 16);
+    assert!(map.cells().all(|(_, last_seen)| last_seen.is_none()));
+
+    map.mark_seen(Vec2::new(125.0, 125.0), 260.0, 7);
+    let seen: Vec<Vec2> = map
+        .cells()
+        .filter(|(_, last_seen)| *last_seen == Some(7))
+        .map(|(pos, _)| pos)
+        .collect();
+    assert_eq!(
+        seen,
+        [
+            Vec2::new(125.0, 125.0),
+            Vec2::new(375.0, 125.0),
+            Vec2::new(125.0, 375.0)
+        ]
+    );
+}
+
+#[test]
+fn idle_swarms_roam_towards_unexplored_regions() {
+    let bounds = Bounds::new(2000.0, 2000.0);
+    let mut explored = ExplorationMap::new(&bounds, 250.0);
+    // everything but the far corner cell was seen a while ago, the surroundings just now
+    explored.mark_seen(Vec2::ZERO, 2600.0, 100);
+    explored.mark_seen(CENTER, 500.0, 1000);
+
+    let decision = RepulsionBrain
+        .decide(&observation(1000, explored.clone()))
+        .unwrap();
+    assert_eq!(decision.intent, Intent::Move);
+    assert_eq!(decision.target, Vec2::new(1875.0, 1875.0));
+
+    // with everything seen equally long ago, stale regions close by win
+    explored.mark_seen(Vec2::ZERO, 3000.0, 100);
+    let target = RepulsionBrain
+        .decide(&observation(1000, explored))
+        .unwrap()
+        .target;
+    assert!(target.distance(CENTER) <= 500.0, "{target}");
+}
+
+#[test]
+fn swarms_remember_beacons_out_of_sight() {
+    let mut sim = empty_sim();
+    let beacon_pos = Vec2::new(1300.0, 1000.0);
+    let beacon = sim.spawn_beacon(beacon_pos, 5);
+    let id = sim.spawn_swarm(Vec2::new(1000.0, 1000.0), 3);
+    sim.swarm_mut(id).unwrap().brain = Arc::new(GotoBrain(Vec2::new(200.0, 1000.0)));
+
+    for _ in 0..300 {
+        sim.step();
+    }
+    let observation = sim.observe(id).unwrap();
+    assert!(observation.beacons.is_empty());
+    let remembered: Vec<_> = observation
+        .remembered_beacons
+        .iter()
+        .map(|b| b.id)
+        .collect();
+    assert_eq!(remembered, [beacon]);
+
+    // without anything in sight, the default brain heads back to regenerate
+    let decision = RepulsionBrain.decide(&observation).unwrap();
+    assert_eq!(decision.target, beacon_pos);
+}
+
+#[test]
+fn camping_swarms_are_revealed() {
+    let mut sim = EmptySim {
+        swarm: SwarmConfig {
+            camping_ticks: 100,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .build();
+    let camper_pos = Vec2::new(2500.0, 2500.0);
+    let camper = sim.spawn_swarm(camper_pos, 5);
+    sim.swarm_mut(camper).unwrap().brain = Arc::new(GotoBrain(camper_pos));
+    let hunter = sim.spawn_swarm(Vec2::new(500.0, 500.0), 5);
+    sim.swarm_mut(hunter).unwrap().brain = Arc::new(GotoBrain(Vec2::new(500.0, 500.0)));
+
+    let seen = |sim: &Simulation| {
+        let observation = sim.observe(hunter).unwrap();
+        observation.contacts.iter().any(|c| c.swarm == camper)
+    };
+    for _ in 0..50 {
+        sim.step();
+    }
+    assert!(!seen(&sim));
+    for _ in 0..200 {
+        sim.step();
+    }
+    assert!(seen(&sim));
+}
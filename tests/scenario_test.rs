@@ -0,0 +1,177 @@
+use swarm_simulation::scenario::{Scenario, ScenarioError};
+use swarm_simulation::spawner::SpawnLocation;
+
+fn assert_invalid(source: &str, expected: &str) {
+    match Scenario::from_toml(source) {
+        Err(ScenarioError::Invalid(msg)) => {
+            assert!(msg.contains(expected), "unexpected message: {msg}")
+        }
+        other => panic!("expected validation error for {expected}, got {other:?}"),
+    }
+}
+
+#[test]
+fn empty_scenario_uses_defaults() {
+    let scenario = Scenario::from_toml("").unwrap();
+    assert_eq!(scenario.swarm.max_ships, 30);
+    assert_eq!(scenario.ship.health, 3);
+    assert!(scenario.swarms.is_empty());
+}
+
+#[test]
+fn scenario_sets_all_configs_and_swarms() {
+    let scenario = Scenario::from_toml(
+        r#"
+        [simulation]
+        seed = 9
+        swarm_size = { kind = "uniform", min = 2, max = 12 }
+
+        [arena]
+        width = 800.0
+        height = 600.0
+
+        [ship]
+        max_speed = 4.0
+
+        [swarm]
+        max_ships = 12
+
+        [[swarms]]
+        pos = [100.0, 200.0]
+        ships = 12
+
+        [[swarms]]
+        pos = [700.0, 400.0]
+        ships = 3
+        "#,
+    )
+    .unwrap();
+
+    let sim = scenario.build();
+    assert_eq!(sim.config().seed, 9);
+    assert_eq!(sim.swarms().len(), 15);
+    assert_eq!(sim.bounds().max.x, 800.0);
+    assert_eq!(sim.ship_config().max_speed, 4.0);
+    assert_eq!(sim.swarm_config().max_ships, 12);
+    assert_eq!(sim.swarms()[0].num_ships(), 12);
+    assert_eq!(sim.swarms()[1].num_ships(), 3);
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    let result = Scenario::from_toml("[ship]\nmax_acel = 1.0\n");
+    assert!(matches!(result, Err(ScenarioError::Parse(_))));
+
+    let result = Scenario::from_toml("[arena]\nwidth = 10.0\ndepth = 3.0\n");
+    assert!(matches!(result, Err(ScenarioError::Parse(_))));
+}
+
+#[test]
+fn out_of_range_values_are_rejected() {
+    assert_invalid("[swarm]\nmax_ships = 0\n", "swarm.max_ships");
+    assert_invalid("[swarm]\nvision_range = 0.0\n", "swarm.vision_range");
+    assert_invalid("[ship]\nmax_accel = -0.5\n", "ship.max_accel");
+    assert_invalid("[arena]\nwidth = 0.0\n", "arena.width");
+    assert_invalid(
+        "[simulation]\nmax_swarms = 2\ninit_swarms = 3\n",
+        "simulation.init_swarms",
+    );
+    assert_invalid(
+        "[[swarms]]\npos = [10.0, 10.0]\nships = 31\n",
+        "swarms[0].ships",
+    );
+    assert_invalid(
+        "[[swarms]]\npos = [-1.0, 10.0]\nships = 3\n",
+        "swarms[0].pos.x",
+    );
+    assert_invalid(
+        "[simulation]\nswarm_size = { kind = \"uniform\", min = 5, max = 3 }\n",
+        "simulation.swarm_size.min",
+    );
+    assert_invalid(
+        "[arena]\nwidth = 1000.0\nheight = 600.0\n[simulation.beacons]\nmargin = 300.0\n",
+        "simulation.beacons.margin",
+    );
+    assert_invalid(
+        "[simulation]\nrespawn_location = { kind = \"interior\", margin = 5000.0 }\n",
+        "simulation.respawn_location.margin",
+    );
+    assert_invalid(
+        "[arena]\nwidth = 1000.0\nheight = 1000.0\n\
+         [simulation]\ninit_location = { kind = \"interior\", margin = 500.0 }\n",
+        "simulation.init_location.margin",
+    );
+    assert_invalid(
+        "[swarm]\nlock_penalty = { at_max = 0.8, exponent = 1.0 }\n",
+        "swarm.lock_penalty.at_max",
+    );
+    assert_invalid(
+        "[swarm]\nspeed_penalty = { at_max = 0.8, exponent = 0.0 }\n",
+        "swarm.speed_penalty.exponent",
+    );
+    assert_invalid(
+        "[swarm.personality]\naggression = { min = 0.5, max = 0.2 }\n",
+        "swarm.personality.aggression.max",
+    );
+    assert_invalid(
+        "[swarm.personality]\nengage_ratio = { min = 0.5, max = 1.0 }\nflee_ratio = { min = 0.9, max = 1.2 }\n",
+        "swarm.personality.engage_ratio.max",
+    );
+    assert_invalid(
+        "[simulation.beacons]\ncapture_lock_factor = 0.5\n",
+        "simulation.beacons.capture_lock_factor",
+    );
+    assert_invalid(
+        "[simulation.beacons]\npayload = { kind = \"fixed\", ships = 0 }\n",
+        "simulation.beacons.payload",
+    );
+}
+
+#[test]
+fn spawn_policies_are_parsed() {
+    let scenario = Scenario::from_toml(
+        r#"
+        [simulation]
+        init_location = { kind = "edge" }
+        respawn_location = { kind = "farthest_from_enemies", margin = 50.0, candidates = 8 }
+        swarm_size = { kind = "fixed", ships = 7 }
+        "#,
+    )
+    .unwrap();
+    assert!(matches!(
+        scenario.simulation.respawn_location,
+        SpawnLocation::FarthestFromEnemies { candidates: 8, .. }
+    ));
+    let sim = scenario.build();
+    assert!(sim.swarms().iter().all(|s| s.num_ships() == 7));
+}
+
+#[test]
+fn bundled_scenarios_are_valid() {
+    Scenario::load(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/scenarios/duel.toml"
+    ))
+    .unwrap();
+    Scenario::load(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/scenarios/battle_royale.toml"
+    ))
+    .unwrap();
+}
+
+#[test]
+fn listed_swarms_join_their_team() {
+    let scenario = Scenario::load(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/scenarios/team_3v3.toml"
+    ))
+    .unwrap();
+    let sim = scenario.build();
+    let teams: Vec<_> = sim.swarms().iter().map(|s| s.team.map(|t| t.0)).collect();
+    assert_eq!(
+        teams,
+        [Some(0), Some(0), Some(0), Some(1), Some(1), Some(1)]
+    );
+    assert_eq!(sim.sides_left(), 2);
+}
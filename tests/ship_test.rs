@@ -1,19 +1,19 @@
 use glam::Vec2;
-use std::rc::Rc;
-use swarm_simulation::ship::{Ship, ShipConfig};
+use std::sync::Arc;
+use swarm_simulation::ship::{Ship, ShipConfig, ShipId};
 
 fn test_ship_reaches_target(config: ShipConfig, target_pos: Vec2, start_velocity: Vec2) {
-    let config = Rc::new(config);
-    let mut ship = Ship::spawn(Vec2::ZERO, config);
+    let config = Arc::new(config);
+    let mut ship = Ship::spawn(ShipId(0), Vec2::ZERO, config);
     ship.vel = start_velocity;
     ship.set_target(target_pos);
 
     let dist = target_pos.length();
     let max_ticks = 100;
 
-    let mut last_dist = dist;
+    let mut _last_dist = dist;
     for tick in 0..max_ticks {
-        ship.movement(1.0);
+        ship.movement(1.0, 1.0);
 
         let dist_to_target = (ship.pos - ship.target_pos).length();
         eprintln!(
@@ -21,13 +21,13 @@ fn test_ship_reaches_target(config: ShipConfig, target_pos: Vec2, start_velocity
             tick, dist_to_target, ship.pos, ship.vel
         );
         // assert!(
-        //     dist_to_target <= last_dist,
+        //     dist_to_target <= _last_dist,
         //     "overshot at tick {}: dist {} > last {}",
         //     tick,
         //     dist_to_target,
-        //     last_dist
+        //     _last_dist
         // );
-        last_dist = dist_to_target;
+        _last_dist = dist_to_target;
     }
 
     let dist_to_target = (ship.pos - ship.target_pos).length();
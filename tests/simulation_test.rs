@@ -0,0 +1,201 @@
+mod common;
+
+use std::collections::HashMap;
+
+use glam::Vec2;
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::spawner::{SpawnLocation, SwarmSize};
+use swarm_simulation::swarm::SwarmId;
+
+/// id, pos, vel, health, lock target, lock progress
+type ShipBits = (u64, [u32; 4], u32, Option<u64>, u32);
+
+/// Flatten the simulation state into comparable bits, so float state is
+/// compared exactly instead of approximately.
+fn state_bits(sim: &Simulation) -> Vec<ShipBits> {
+    sim.swarms()
+        .iter()
+        .flat_map(|swarm| swarm.ships.iter())
+        .map(|(ship, _)| {
+            (
+                ship.id.0,
+                [
+                    ship.pos.x.to_bits(),
+                    ship.pos.y.to_bits(),
+                    ship.vel.x.to_bits(),
+                    ship.vel.y.to_bits(),
+                ],
+                ship.health,
+                ship.lock_target.map(|id| id.0),
+                ship.lock_progress,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn same_seed_gives_identical_state() {
+    let mut a = common::seeded_sim(42);
+    let mut b = common::seeded_sim(42);
+    assert_eq!(state_bits(&a), state_bits(&b));
+
+    for tick in 0..600 {
+        a.step();
+        b.step();
+        assert_eq!(state_bits(&a), state_bits(&b), "diverged at tick {tick}");
+    }
+}
+
+#[test]
+fn different_seeds_give_different_state() {
+    let a = common::seeded_sim(1);
+    let b = common::seeded_sim(2);
+    assert_ne!(state_bits(&a), state_bits(&b));
+}
+
+#[test]
+fn ship_ids_are_allocated_per_simulation() {
+    let a = common::seeded_sim(7);
+    let b = common::seeded_sim(7);
+    let first_id = |sim: &Simulation| sim.swarms()[0].ships[0].0.id;
+    assert_eq!(first_id(&a), first_id(&b));
+    assert_eq!(first_id(&a).0, 0);
+}
+
+#[test]
+fn stats_account_for_every_destroyed_ship() {
+    let mut sim = common::seeded_sim(3);
+    let spawned: u32 = sim.swarms().iter().map(|s| s.stats.spawned_ships).sum();
+
+    for _ in 0..3000 {
+        sim.step();
+    }
+
+    let alive_stats = sim.swarms().iter().map(|s| &s.stats);
+    let eliminated_stats = sim.eliminated().iter().map(|(_, stats)| stats);
+    let all_stats: Vec<_> = alive_stats.chain(eliminated_stats).collect();
+    let kills: u32 = all_stats.iter().map(|s| s.kills).sum();
+    let shots: u32 = all_stats.iter().map(|s| s.shots_fired).sum();
+    let reinforcements: u32 = all_stats.iter().map(|s| s.reinforcements).sum();
+    let survivors: u32 = sim.swarms().iter().map(|s| s.num_ships()).sum();
+
+    assert_eq!(all_stats.len(), 10);
+    assert_eq!(kills, spawned + reinforcements - survivors);
+    assert!(shots >= kills);
+}
+
+#[test]
+fn respawn_fills_up_to_max_swarms() {
+    let mut sim = Simulation::new(
+        SimulationConfig {
+            max_swarms: 3,
+            init_swarms: 1,
+            respawn_interval: 10,
+            swarm_size: SwarmSize::Fixed { ships: 4 },
+            ..Default::default()
+        },
+        Bounds::new(5000.0, 5000.0),
+    );
+    sim.spawn_initial_swarms();
+    assert_eq!(sim.swarms().len(), 1);
+
+    for _ in 0..9 {
+        sim.step();
+    }
+    assert_eq!(sim.swarms().len(), 1);
+    sim.step();
+    assert_eq!(sim.swarms().len(), 2);
+
+    for _ in 0..100 {
+        sim.step();
+    }
+    assert_eq!(sim.swarms().len(), 3);
+    assert!(sim.swarms().iter().all(|s| s.num_ships() == 4));
+}
+
+#[test]
+fn oversized_swarms_spawn_with_max_ships() {
+    let mut sim = Simulation::new(
+        SimulationConfig {
+            init_swarms: 2,
+            swarm_size: SwarmSize::Fixed { ships: 100 },
+            ..Default::default()
+        },
+        Bounds::new(5000.0, 5000.0),
+    );
+    sim.spawn_initial_swarms();
+    let max_ships = sim.swarm_config().max_ships;
+    assert!(sim.swarms().iter().all(|s| s.num_ships() == max_ships));
+    let id = sim.spawn_swarm(Vec2::new(100.0, 100.0), max_ships + 1);
+    assert_eq!(sim.swarm(id).unwrap().num_ships(), max_ships);
+}
+
+#[test]
+fn edge_spawns_are_on_the_walls() {
+    let mut sim = Simulation::new(SimulationConfig::default(), Bounds::new(1000.0, 800.0));
+    for _ in 0..20 {
+        let id = sim.spawn_random_swarm(SpawnLocation::Edge);
+        let target = sim.swarm(id).unwrap().target_pos;
+        let on_wall = target.x == 0.0 || target.x == 1000.0 || target.y == 0.0 || target.y == 800.0;
+        assert!(on_wall, "{target} is not on a wall");
+    }
+}
+
+#[test]
+fn oversized_margins_spawn_in_the_center() {
+    let mut sim = Simulation::new(SimulationConfig::default(), Bounds::new(1000.0, 800.0));
+    let id = sim.spawn_random_swarm(SpawnLocation::Interior { margin: 500.0 });
+    assert_eq!(sim.swarm(id).unwrap().target_pos, Vec2::new(500.0, 400.0));
+    let id = sim.spawn_random_swarm(SpawnLocation::Interior { margin: 400.0 });
+    assert_eq!(sim.swarm(id).unwrap().target_pos.y, 400.0);
+}
+
+#[test]
+fn farthest_spawn_avoids_existing_swarms() {
+    let mut sim = Simulation::new(SimulationConfig::default(), Bounds::new(2000.0, 2000.0));
+    sim.spawn_swarm(Vec2::new(100.0, 100.0), 5);
+
+    for _ in 0..10 {
+        let id = sim.spawn_random_swarm(SpawnLocation::FarthestFromEnemies {
+            margin: 100.0,
+            candidates: 32,
+        });
+        let pos = sim.swarm(id).unwrap().target_pos;
+        let nearest = sim
+            .swarms()
+            .iter()
+            .filter(|s| s.id != id)
+            .map(|s| s.center.distance(pos))
+            .fold(f32::MAX, f32::min);
+        assert!(nearest > 300.0, "spawned {nearest} away from another swarm");
+    }
+}
+
+#[test]
+fn swarm_ids_stay_stable_when_swarms_die() {
+    let mut sim = common::seeded_sim(5);
+    let ids: Vec<SwarmId> = sim.swarms().iter().map(|s| s.id).collect();
+    assert_eq!(ids, (0..10).map(SwarmId).collect::<Vec<_>>());
+
+    // remember which swarm every ship belongs to, this must not change
+    let owners: HashMap<_, _> = sim
+        .swarms()
+        .iter()
+        .flat_map(|s| s.ships.iter().map(move |(ship, _)| (ship.id, s.id)))
+        .collect();
+
+    while sim.eliminated().is_empty() {
+        sim.step();
+    }
+
+    // ships added by beacons since then are new, but no ship may change swarms
+    for swarm in sim.swarms() {
+        for (ship, _) in &swarm.ships {
+            assert!(owners.get(&ship.id).is_none_or(|owner| *owner == swarm.id));
+        }
+    }
+
+    let dead = sim.eliminated()[0].0;
+    assert!(sim.swarm(dead).is_none());
+    assert!(sim.swarm_mut(dead).is_none());
+}
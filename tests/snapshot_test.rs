@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use glam::Vec2;
+use swarm_simulation::brain::{BrainRegistry, RepulsionBrain, SwarmBrain};
+use swarm_simulation::observation::Observation;
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::snapshot::{SNAPSHOT_VERSION, SimulationSnapshot, SnapshotError};
+use swarm_simulation::swarm::{Intent, SwarmDecision, SwarmId};
+
+fn seeded_sim(seed: u64) -> Simulation {
+    let mut sim = Simulation::new(
+        SimulationConfig {
+            seed,
+            max_swarms: 12,
+            init_swarms: 8,
+            respawn_interval: 120,
+            ..Default::default()
+        },
+        Bounds::new(1500.0, 1500.0),
+    );
+    sim.spawn_initial_swarms();
+    sim
+}
+
+/// Always heads for the same point
+struct GotoBrain(Vec2);
+
+impl SwarmBrain for GotoBrain {
+    fn name(&self) -> &str {
+        "goto"
+    }
+
+    fn decide(&self, _observation: &Observation) -> Option<SwarmDecision> {
+        Some(SwarmDecision {
+            target: self.0,
+            intent: Intent::Move,
+        })
+    }
+}
+
+/// Every other swarm heads for the center
+fn goto_or_repulsion(id: SwarmId) -> Arc<dyn SwarmBrain> {
+    if id.0.is_multiple_of(2) {
+        Arc::new(GotoBrain(Vec2::new(750.0, 750.0)))
+    } else {
+        Arc::new(RepulsionBrain)
+    }
+}
+
+/// Encoded snapshots compare every piece of state bit-exact
+fn state(sim: &Simulation) -> Vec<u8> {
+    sim.snapshot().to_bytes().unwrap()
+}
+
+#[test]
+fn restored_simulation_continues_identically() {
+    let mut original = seeded_sim(21);
+    for _ in 0..400 {
+        original.step();
+    }
+
+    let bytes = state(&original);
+    let mut restored =
+        Simulation::from_snapshot(SimulationSnapshot::from_bytes(&bytes).unwrap()).unwrap();
+    assert_eq!(state(&restored), bytes);
+
+    for tick in 0..1500 {
+        original.step();
+        restored.step();
+        assert_eq!(
+            state(&original),
+            state(&restored),
+            "diverged at tick {tick}"
+        );
+    }
+    assert_eq!(original.tick(), restored.tick());
+    assert_eq!(original.eliminated().len(), restored.eliminated().len());
+}
+
+#[test]
+fn ship_and_swarm_ids_continue_after_restore() {
+    let mut original = seeded_sim(4);
+    let mut restored = Simulation::from_snapshot(original.snapshot()).unwrap();
+
+    let a = original.spawn_swarm(glam::Vec2::new(500.0, 500.0), 3);
+    let b = restored.spawn_swarm(glam::Vec2::new(500.0, 500.0), 3);
+    assert_eq!(a, b);
+    let ship_ids = |sim: &Simulation| -> Vec<u64> {
+        let swarm = sim.swarm(a).unwrap();
+        swarm.ships.iter().map(|(ship, _)| ship.id.0).collect()
+    };
+    assert_eq!(ship_ids(&original), ship_ids(&restored));
+}
+
+#[test]
+fn snapshot_file_roundtrip() {
+    let mut sim = seeded_sim(8);
+    for _ in 0..100 {
+        sim.step();
+    }
+
+    let path = std::env::temp_dir().join("swarm_simulation_snapshot_test.bin");
+    sim.snapshot().save(&path).unwrap();
+    let loaded = SimulationSnapshot::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        state(&Simulation::from_snapshot(loaded).unwrap()),
+        state(&sim)
+    );
+}
+
+#[test]
+fn incompatible_snapshots_are_rejected() {
+    let mut snapshot = seeded_sim(1).snapshot();
+    snapshot.version = SNAPSHOT_VERSION + 1;
+    let bytes = snapshot.to_bytes().unwrap();
+    assert!(matches!(
+        SimulationSnapshot::from_bytes(&bytes),
+        Err(SnapshotError::Version(_))
+    ));
+
+    // the version is checked before the rest, whose layout may differ
+    assert!(matches!(
+        SimulationSnapshot::from_bytes(&bytes[..bytes.len() / 2]),
+        Err(SnapshotError::Version(_))
+    ));
+
+    let bytes = seeded_sim(1).snapshot().to_bytes().unwrap();
+    assert!(matches!(
+        SimulationSnapshot::from_bytes(&bytes[..bytes.len() / 2]),
+        Err(SnapshotError::Encoding(_))
+    ));
+}
+
+#[test]
+fn custom_brains_are_restored_by_name() {
+    let mut original = Simulation::new(
+        SimulationConfig {
+            seed: 4,
+            max_swarms: 10,
+            init_swarms: 6,
+            respawn_interval: 60,
+            ..Default::default()
+        },
+        Bounds::new(1500.0, 1500.0),
+    );
+    original.set_brain_factory(goto_or_repulsion);
+    original.spawn_initial_swarms();
+    for _ in 0..300 {
+        original.step();
+    }
+    let snapshot = original.snapshot();
+
+    assert!(matches!(
+        Simulation::from_snapshot(snapshot.clone()),
+        Err(SnapshotError::MissingBrainFactory)
+    ));
+    let mut brains = BrainRegistry::default();
+    brains.set_factory(goto_or_repulsion);
+    assert!(matches!(
+        Simulation::from_snapshot_with_brains(snapshot.clone(), &brains),
+        Err(SnapshotError::UnknownBrain(name)) if name == "goto"
+    ));
+
+    brains.register(Arc::new(GotoBrain(Vec2::new(750.0, 750.0))));
+    let mut restored = Simulation::from_snapshot_with_brains(snapshot, &brains).unwrap();
+    for _ in 0..600 {
+        original.step();
+        restored.step();
+    }
+    assert_eq!(state(&restored), state(&original));
+}
@@ -0,0 +1,48 @@
+use glam::Vec2;
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use swarm_simulation::spatial::SpatialGrid;
+
+#[test]
+fn grid_queries_find_everything_in_range() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(1);
+    let points: Vec<Vec2> = (0..2000)
+        .map(|_| {
+            Vec2::new(
+                rng.random_range(-500.0..3000.0),
+                rng.random_range(-500.0..3000.0),
+            )
+        })
+        .collect();
+    let mut grid = SpatialGrid::new(250.0);
+    for (idx, pos) in points.iter().enumerate() {
+        grid.insert(*pos, idx);
+    }
+
+    for (pos, range) in [
+        (Vec2::new(1000.0, 1000.0), 500.0),
+        (Vec2::new(0.0, 0.0), 100.0),
+        (Vec2::new(-400.0, 2900.0), 900.0),
+        (Vec2::new(1234.5, 17.0), 0.0),
+    ] {
+        let mut found: Vec<usize> = grid.query(pos, range).collect();
+        found.sort_unstable();
+        let expected: Vec<usize> = (0..points.len())
+            .filter(|idx| points[*idx].distance_squared(pos) <= range * range)
+            .collect();
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn grid_queries_cross_cell_borders() {
+    let mut grid = SpatialGrid::new(100.0);
+    grid.insert(Vec2::new(99.0, 99.0), 'a');
+    grid.insert(Vec2::new(101.0, 101.0), 'b');
+    grid.insert(Vec2::new(-0.5, 100.0), 'c');
+    grid.insert(Vec2::new(300.0, 300.0), 'd');
+
+    let mut found: Vec<char> = grid.query(Vec2::new(100.0, 100.0), 101.0).collect();
+    found.sort_unstable();
+    assert_eq!(found, ['a', 'b', 'c']);
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::empty_sim;
+use glam::Vec2;
+use swarm_simulation::event::EventKind;
+use swarm_simulation::ship::ShipId;
+use swarm_simulation::swarm::{Swarm, SwarmId, TeamId};
+
+fn ship_ids(swarm: &Swarm) -> HashSet<ShipId> {
+    swarm.ships.iter().map(|(ship, _)| ship.id).collect()
+}
+
+#[test]
+fn split_swarms_keep_their_ships() {
+    let mut sim = empty_sim();
+    let id = sim.spawn_team_swarm(Vec2::new(1000.0, 1000.0), 10, Some(TeamId(1)));
+    for (ship, _) in &mut sim.swarm_mut(id).unwrap().ships {
+        ship.health = 2;
+        ship.lock_progress = 7;
+    }
+    let before = ship_ids(sim.swarm(id).unwrap());
+    sim.drain_events();
+
+    let new_id = sim.split_swarm(id, 4).unwrap();
+    let (swarm, new_swarm) = (sim.swarm(id).unwrap(), sim.swarm(new_id).unwrap());
+    assert_eq!((swarm.num_ships(), new_swarm.num_ships()), (6, 4));
+    assert!(ship_ids(swarm).is_disjoint(&ship_ids(new_swarm)));
+    assert_eq!(&ship_ids(swarm) | &ship_ids(new_swarm), before);
+    assert!(
+        new_swarm
+            .ships
+            .iter()
+            .all(|(ship, _)| ship.health == 2 && ship.lock_progress == 7)
+    );
+    assert_eq!(new_swarm.team, Some(TeamId(1)));
+    assert_eq!(new_swarm.personality, swarm.personality);
+    assert_eq!(new_swarm.stats.parent, Some(id));
+    // split off ships aren't spawned twice
+    assert_eq!(swarm.stats.spawned_ships, 10);
+    assert_eq!(new_swarm.stats.spawned_ships, 0);
+
+    // the formation faces right, so the left flank is the top
+    assert!(new_swarm.center.y > swarm.center.y);
+    let events: Vec<_> = sim.drain_events().map(|event| event.kind).collect();
+    assert_eq!(
+        events,
+        [EventKind::SwarmSplit {
+            swarm: id,
+            new_swarm: new_id,
+            num_ships: 4,
+        }]
+    );
+
+    // swarms can't be split into nothing
+    assert_eq!(sim.split_swarm(id, 0), None);
+    assert_eq!(sim.split_swarm(id, 6), None);
+    assert_eq!(sim.split_swarm(SwarmId(99), 1), None);
+}
+
+#[test]
+fn split_swarms_move_on_their_own() {
+    let mut sim = empty_sim();
+    let id = sim.spawn_swarm(Vec2::new(1000.0, 1000.0), 20);
+    let new_id = sim.split_swarm(id, 10).unwrap();
+    sim.swarm_mut(id)
+        .unwrap()
+        .set_target(Vec2::new(500.0, 1000.0));
+    sim.swarm_mut(new_id)
+        .unwrap()
+        .set_target(Vec2::new(1500.0, 1000.0));
+
+    for _ in 0..30 {
+        sim.step();
+    }
+    let distance = sim
+        .swarm(id)
+        .unwrap()
+        .center
+        .distance(sim.swarm(new_id).unwrap().center);
+    assert!(distance > 100.0, "{distance}");
+
+    // every ship is gathered in the formation around its own swarm
+    for swarm in sim.swarms() {
+        for (ship, relative_pos) in &swarm.ships {
+            assert_eq!(ship.target_pos, swarm.target_pos + *relative_pos);
+        }
+    }
+}
+
+#[test]
+fn allied_swarms_merge() {
+    let mut sim = empty_sim();
+    let a = sim.spawn_team_swarm(Vec2::new(1000.0, 1000.0), 5, Some(TeamId(0)));
+    let b = sim.spawn_team_swarm(Vec2::new(1100.0, 1000.0), 7, Some(TeamId(0)));
+    let enemy = sim.spawn_team_swarm(Vec2::new(1200.0, 1000.0), 5, Some(TeamId(1)));
+    let before = &ship_ids(sim.swarm(a).unwrap()) | &ship_ids(sim.swarm(b).unwrap());
+    sim.step();
+    sim.drain_events();
+
+    assert!(sim.merge_swarms(a, b));
+    let swarm = sim.swarm(a).unwrap();
+    assert_eq!(ship_ids(swarm), before);
+    assert_eq!(swarm.stats.reinforcements, 7);
+    assert!(sim.swarm(b).is_none());
+    let (merged, stats) = sim.eliminated().last().unwrap();
+    assert_eq!((*merged, stats.merged_into), (b, Some(a)));
+
+    // relative positions are rebuilt, no two ships share a slot
+    let slots: HashSet<_> = swarm
+        .ships
+        .iter()
+        .map(|(_, pos)| (pos.x.to_bits(), pos.y.to_bits()))
+        .collect();
+    assert_eq!(slots.len(), 12);
+
+    let events: Vec<_> = sim.drain_events().map(|event| event.kind).collect();
+    assert_eq!(
+        events,
+        [EventKind::SwarmsMerged {
+            swarm: a,
+            merged: b,
+            num_ships: 7,
+        }]
+    );
+
+    // enemies, unknown swarms and swarms that would get too big don't merge
+    assert!(!sim.merge_swarms(a, enemy));
+    assert!(!sim.merge_swarms(a, a));
+    assert!(!sim.merge_swarms(a, b));
+    let big = sim.spawn_team_swarm(Vec2::new(1000.0, 1100.0), 20, Some(TeamId(0)));
+    assert!(!sim.merge_swarms(a, big));
+    assert_eq!(sim.swarms().len(), 3);
+}
+
+#[test]
+fn split_halves_without_a_team_stay_allies() {
+    let split = || {
+        let mut sim = empty_sim();
+        let id = sim.spawn_swarm(Vec2::new(1000.0, 1000.0), 10);
+        let half = sim.split_swarm(id, 5).unwrap();
+        (sim, id, half)
+    };
+
+    // point-blank halves never shoot each other
+    let (mut sim, _, _) = split();
+    assert_eq!(sim.sides_left(), 1);
+    for _ in 0..100 {
+        sim.step();
+    }
+    assert!(
+        !sim.drain_events()
+            .any(|event| matches!(event.kind, EventKind::ShotFired { .. }))
+    );
+
+    let (mut sim, id, half) = split();
+
+    let stranger = sim.spawn_swarm(Vec2::new(1000.0, 1300.0), 5);
+    let swarm = sim.swarm(id).unwrap();
+    assert!(swarm.is_ally(sim.swarm(half).unwrap()));
+    assert!(!swarm.is_ally(sim.swarm(stranger).unwrap()));
+    assert_eq!(sim.sides_left(), 2);
+    let observation = sim.observe(id).unwrap();
+    let allies: Vec<_> = observation.allies.iter().map(|ally| ally.swarm).collect();
+    let contacts: Vec<_> = observation.contacts.iter().map(|c| c.swarm).collect();
+    assert_eq!((allies, contacts), (vec![half], vec![stranger]));
+
+    // only the halves merge
+    assert!(!sim.merge_swarms(id, stranger));
+    assert!(sim.merge_swarms(id, half));
+}
+
+#[test]
+fn distant_allies_do_not_merge() {
+    let mut sim = empty_sim();
+    let a = sim.spawn_team_swarm(Vec2::new(500.0, 500.0), 5, Some(TeamId(0)));
+    let b = sim.spawn_team_swarm(Vec2::new(2500.0, 500.0), 5, Some(TeamId(0)));
+    assert!(!sim.merge_swarms(a, b));
+    assert_eq!(sim.swarms().len(), 2);
+}
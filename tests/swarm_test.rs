@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use glam::Vec2;
+use swarm_simulation::ship::{ShipConfig, ShipIdAllocator};
+use swarm_simulation::simulation::Bounds;
+use swarm_simulation::swarm::{GrowthCurve, Swarm, SwarmConfig, SwarmId};
+
+fn spawn_swarm(num_ships: u32, config: SwarmConfig) -> (Swarm, ShipIdAllocator) {
+    let mut ship_ids = ShipIdAllocator::default();
+    let swarm = Swarm::spawn(
+        SwarmId(0),
+        Vec2::new(500.0, 500.0),
+        num_ships,
+        config.personality.midpoint(),
+        &mut ship_ids,
+        Arc::new(config),
+        Arc::new(ShipConfig::default()),
+    );
+    (swarm, ship_ids)
+}
+
+fn min_ship_distance(swarm: &Swarm) -> f32 {
+    let mut min = f32::MAX;
+    for (i, (_, a)) in swarm.ships.iter().enumerate() {
+        for (_, b) in &swarm.ships[i + 1..] {
+            min = min.min(a.distance(*b));
+        }
+    }
+    min
+}
+
+#[test]
+fn add_ships_respects_max_ships() {
+    let config = SwarmConfig {
+        max_ships: 10,
+        ..Default::default()
+    };
+    let (mut swarm, mut ship_ids) = spawn_swarm(8, config);
+
+    let added = swarm.add_ships(5, &mut ship_ids, Arc::new(ShipConfig::default()));
+    assert_eq!(added, 2);
+    assert_eq!(swarm.num_ships(), 10);
+    assert_eq!(swarm.stats.reinforcements, 2);
+
+    let added = swarm.add_ships(1, &mut ship_ids, Arc::new(ShipConfig::default()));
+    assert_eq!(added, 0);
+}
+
+#[test]
+fn spawn_respects_max_ships() {
+    let config = SwarmConfig {
+        max_ships: 10,
+        ..Default::default()
+    };
+    let (swarm, _) = spawn_swarm(25, config);
+    assert_eq!(swarm.num_ships(), 10);
+}
+
+#[test]
+fn add_ships_reuses_free_slots() {
+    let (mut swarm, mut ship_ids) = spawn_swarm(12, SwarmConfig::default());
+    let original: Vec<Vec2> = swarm.ships.iter().map(|(_, pos)| *pos).collect();
+
+    // destroy the inner ships, their slots should be filled again
+    swarm.ships.drain(..4);
+    swarm.add_ships(4, &mut ship_ids, Arc::new(ShipConfig::default()));
+
+    let mut refilled: Vec<Vec2> = swarm.ships[8..].iter().map(|(_, pos)| *pos).collect();
+    refilled.sort_by(|a, b| a.length().partial_cmp(&b.length()).unwrap());
+    for (slot, pos) in original[..4].iter().zip(&refilled) {
+        assert!(slot.distance(*pos) < 0.001, "{slot} != {pos}");
+    }
+    assert!(min_ship_distance(&swarm) > swarm.config.scale * 0.5);
+
+    // new ships get unique IDs
+    let ids: std::collections::HashSet<_> = swarm.ships.iter().map(|(s, _)| s.id).collect();
+    assert_eq!(ids.len(), 12);
+}
+
+#[test]
+fn growth_curves_scale_with_occupancy() {
+    let linear = GrowthCurve {
+        at_max: 0.5,
+        exponent: 1.0,
+    };
+    assert_eq!(linear.factor(0.0), 1.0);
+    assert_eq!(linear.factor(0.5), 0.75);
+    assert_eq!(linear.factor(1.0), 0.5);
+
+    let late = GrowthCurve {
+        at_max: 2.0,
+        exponent: 2.0,
+    };
+    assert_eq!(late.factor(0.5), 1.25);
+
+    let config = SwarmConfig {
+        max_ships: 11,
+        ..Default::default()
+    };
+    let (single, _) = spawn_swarm(1, config.clone());
+    let (half, _) = spawn_swarm(6, config.clone());
+    let (full, _) = spawn_swarm(11, config);
+    assert_eq!(single.occupancy(), 0.0);
+    assert_eq!(half.occupancy(), 0.5);
+    assert_eq!(full.growth_factor(&linear), 0.5);
+}
+
+#[test]
+fn formation_turns_with_limited_rate() {
+    let config = SwarmConfig {
+        max_ships: 10,
+        max_turn_rate: 0.2,
+        turn_penalty: GrowthCurve {
+            at_max: 0.5,
+            exponent: 1.0,
+        },
+        ..Default::default()
+    };
+    let (mut small, _) = spawn_swarm(1, config.clone());
+    let (mut large, _) = spawn_swarm(10, config);
+    let bounds = Bounds::new(2000.0, 2000.0);
+
+    // target straight up, a quarter turn away
+    for swarm in [&mut small, &mut large] {
+        swarm.set_target(Vec2::new(500.0, 1000.0));
+        swarm.movement(&bounds, &mut Vec::new());
+    }
+    assert!((small.direction - 0.2).abs() < 1e-6);
+    assert!((large.direction - 0.1).abs() < 1e-6);
+
+    for _ in 0..20 {
+        large.movement(&bounds, &mut Vec::new());
+    }
+    assert!((large.direction - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::empty_sim;
+use glam::Vec2;
+use swarm_simulation::brain::{RepulsionBrain, SwarmBrain};
+use swarm_simulation::event::EventKind;
+use swarm_simulation::observation::{Ally, Contact, Observation, OwnSwarm};
+use swarm_simulation::personality::PersonalityConfig;
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::swarm::{Intent, SwarmDecision, SwarmId, TeamId};
+
+/// Always heads for the same point
+struct GotoBrain(Vec2);
+
+impl SwarmBrain for GotoBrain {
+    fn name(&self) -> &str {
+        "goto"
+    }
+
+    fn decide(&self, _observation: &Observation) -> Option<SwarmDecision> {
+        Some(SwarmDecision {
+            target: self.0,
+            intent: Intent::Move,
+        })
+    }
+}
+
+/// Two resting swarms right next to each other, returns the number of shots fired
+fn shots_between(team_a: Option<TeamId>, team_b: Option<TeamId>) -> usize {
+    let mut sim = empty_sim();
+    for (pos, team) in [(1000.0, team_a), (1100.0, team_b)] {
+        let pos = Vec2::new(pos, 1000.0);
+        let id = sim.spawn_team_swarm(pos, 5, team);
+        sim.swarm_mut(id).unwrap().brain = Arc::new(GotoBrain(pos));
+    }
+    sim.drain_events();
+
+    let mut shots = 0;
+    for _ in 0..300 {
+        sim.step();
+        shots += sim
+            .drain_events()
+            .filter(|event| matches!(event.kind, EventKind::ShotFired { .. }))
+            .count();
+    }
+    shots
+}
+
+#[test]
+fn allies_never_shoot_each_other() {
+    assert_eq!(shots_between(Some(TeamId(1)), Some(TeamId(1))), 0);
+    assert!(shots_between(Some(TeamId(1)), Some(TeamId(2))) > 0);
+    assert!(shots_between(None, None) > 0);
+}
+
+#[test]
+fn allies_share_vision() {
+    let mut sim = empty_sim();
+    let me = sim.spawn_team_swarm(Vec2::new(1000.0, 1000.0), 5, Some(TeamId(0)));
+    let ally = sim.spawn_team_swarm(Vec2::new(1400.0, 1000.0), 5, Some(TeamId(0)));
+    let enemy = sim.spawn_team_swarm(Vec2::new(1800.0, 1000.0), 5, Some(TeamId(1)));
+    let loner = sim.spawn_swarm(Vec2::new(1000.0, 1800.0), 5);
+
+    let observation = sim.observe(me).unwrap();
+    let contacts: Vec<_> = observation.contacts.iter().map(|c| c.swarm).collect();
+    assert_eq!(contacts, [enemy]);
+    assert_eq!(observation.contacts[0].distance, 800.0);
+    let allies: Vec<_> = observation.allies.iter().map(|a| a.swarm).collect();
+    assert_eq!(allies, [ally]);
+    assert_eq!(observation.ships.len(), 5);
+
+    // swarms without a team see on their own
+    assert!(sim.observe(loner).unwrap().allies.is_empty());
+    assert!(sim.observe(loner).unwrap().contacts.is_empty());
+}
+
+#[test]
+fn random_swarms_are_spread_over_teams() {
+    let mut sim = Simulation::new(
+        SimulationConfig {
+            init_swarms: 5,
+            teams: 2,
+            ..Default::default()
+        },
+        Bounds::new(2000.0, 2000.0),
+    );
+    sim.spawn_initial_swarms();
+
+    let teams: Vec<_> = sim.swarms().iter().map(|s| s.team.unwrap().0).collect();
+    assert_eq!(teams, [0, 1, 0, 1, 0]);
+    assert_eq!(sim.sides_left(), 2);
+    assert!(sim.swarms()[0].is_ally(&sim.swarms()[2]));
+    assert!(!sim.swarms()[0].is_ally(&sim.swarms()[1]));
+}
+
+const CENTER: Vec2 = Vec2::new(1000.0, 1000.0);
+
+fn observation(contacts: Vec<Contact>, allies: Vec<Ally>, coordination: bool) -> Observation {
+    let mut personality = PersonalityConfig::default().midpoint();
+    personality.risk_tolerance = 0.0;
+    Observation {
+        tick: 0,
+        own: OwnSwarm {
+            id: SwarmId(0),
+            team: Some(TeamId(0)),
+            center: CENTER,
+            velocity: Vec2::ZERO,
+            target_pos: CENTER,
+            direction: 0.0,
+            num_ships: 10,
+            max_ships: 30,
+            occupancy: 0.33,
+            vision_range: 500.0,
+            max_speed: 10.0,
+            max_accel: 0.3,
+            kite_distance: 275.0,
+            memory_ticks: 180,
+            roam_interval: 0,
+            coordination,
+            personality,
+        },
+        bounds: Bounds::new(4000.0, 4000.0),
+        contacts,
+        allies,
+        ships: Vec::new(),
+        beacons: Vec::new(),
+        remembered: Vec::new(),
+        remembered_beacons: Vec::new(),
+        explored: Default::default(),
+    }
+}
+
+fn contact(swarm: u64, center: Vec2, num_ships: u32) -> Contact {
+    Contact {
+        swarm: SwarmId(swarm),
+        center,
+        velocity: Vec2::ZERO,
+        num_ships,
+        max_speed: 10.0,
+        distance: center.distance(CENTER),
+    }
+}
+
+fn ally(swarm: u64, center: Vec2, target_pos: Vec2, intent: Intent) -> Ally {
+    Ally {
+        swarm: SwarmId(swarm),
+        center,
+        velocity: Vec2::ZERO,
+        num_ships: 10,
+        target_pos,
+        intent,
+        distance: center.distance(CENTER),
+    }
+}
+
+#[test]
+fn coordinated_swarms_flee_from_threats_near_allies() {
+    // out of our threat range, but close to our ally
+    let threat = contact(5, Vec2::new(1700.0, 1000.0), 30);
+    let ally = ally(
+        1,
+        Vec2::new(1300.0, 1000.0),
+        Vec2::new(900.0, 1100.0),
+        Intent::Flee,
+    );
+
+    let decide = |coordination| {
+        RepulsionBrain.decide(&observation(
+            vec![threat.clone()],
+            vec![ally.clone()],
+            coordination,
+        ))
+    };
+    let coordinated = decide(true).unwrap();
+    assert_eq!(coordinated.intent, Intent::Flee);
+    assert!(coordinated.target.x < CENTER.x);
+    assert!(!decide(false).is_some_and(|decision| decision.intent == Intent::Flee));
+}
+
+#[test]
+fn coordinated_swarms_hunt_the_prey_of_their_allies() {
+    let near = contact(5, Vec2::new(1250.0, 1000.0), 3);
+    let farther = contact(6, Vec2::new(1000.0, 1300.0), 3);
+    let hunter = ally(1, Vec2::new(800.0, 1200.0), farther.center, Intent::Move);
+
+    let decide = |coordination| {
+        RepulsionBrain
+            .decide(&observation(
+                vec![near.clone(), farther.clone()],
+                vec![hunter.clone()],
+                coordination,
+            ))
+            .unwrap()
+            .target
+    };
+    assert_eq!(decide(false), near.center);
+    assert_eq!(decide(true), farther.center);
+}
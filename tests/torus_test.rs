@@ -0,0 +1,104 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::EmptySim;
+use glam::Vec2;
+use swarm_simulation::event::EventKind;
+use swarm_simulation::scenario::Scenario;
+use swarm_simulation::ship::{ShipConfig, ShipIdAllocator};
+use swarm_simulation::simulation::Bounds;
+use swarm_simulation::swarm::{Swarm, SwarmConfig, SwarmId};
+
+fn torus() -> Bounds {
+    Bounds::new(1000.0, 1000.0).with_wrap_around(true)
+}
+
+#[test]
+fn distances_are_measured_across_edges() {
+    let bounds = torus();
+    let a = Vec2::new(20.0, 500.0);
+    let b = Vec2::new(980.0, 990.0);
+    assert_eq!(bounds.delta(a, b), Vec2::new(-40.0, 490.0));
+    assert_eq!(bounds.distance(a, Vec2::new(980.0, 500.0)), 40.0);
+    assert_eq!(bounds.nearest_image(a, b), Vec2::new(-20.0, 990.0));
+    assert_eq!(
+        bounds.wrap(Vec2::new(-20.0, 1010.0)),
+        Vec2::new(980.0, 10.0)
+    );
+    assert_eq!(bounds.images(Vec2::new(500.0, 500.0), 100.0).len(), 1);
+    assert_eq!(bounds.images(Vec2::new(20.0, 20.0), 100.0).len(), 4);
+
+    let walled = Bounds::new(1000.0, 1000.0);
+    assert_eq!(walled.distance(a, Vec2::new(980.0, 500.0)), 960.0);
+    assert_eq!(
+        walled.wrap(Vec2::new(-20.0, 1010.0)),
+        Vec2::new(-20.0, 1010.0)
+    );
+}
+
+#[test]
+fn swarms_take_the_short_way_across_edges() {
+    let bounds = torus();
+    let config = SwarmConfig::default();
+    let mut swarm = Swarm::spawn(
+        SwarmId(0),
+        Vec2::new(50.0, 500.0),
+        10,
+        config.personality.midpoint(),
+        &mut ShipIdAllocator::default(),
+        Arc::new(config),
+        Arc::new(ShipConfig::default()),
+    );
+    swarm.set_target(bounds.nearest_image(swarm.center, Vec2::new(900.0, 500.0)));
+
+    for _ in 0..300 {
+        swarm.movement(&bounds, &mut Vec::new());
+        swarm.finalize(&bounds);
+        for (ship, _) in &swarm.ships {
+            assert!(ship.pos.cmpge(bounds.min).all() && ship.pos.cmplt(bounds.max).all());
+        }
+        // never through the middle of the arena
+        assert!(swarm.center.x < 200.0 || swarm.center.x > 800.0);
+        // crossing the edge is not a jump across the arena
+        assert!(swarm.velocity.length() < 50.0, "{}", swarm.velocity);
+    }
+    assert!(bounds.distance(swarm.center, Vec2::new(900.0, 500.0)) < 5.0);
+}
+
+#[test]
+fn swarms_see_and_fight_across_edges() {
+    let mut sim = EmptySim {
+        bounds: torus(),
+        ..Default::default()
+    }
+    .build();
+    let left = sim.spawn_swarm(Vec2::new(30.0, 500.0), 3);
+    let right = sim.spawn_swarm(Vec2::new(970.0, 500.0), 3);
+
+    let observation = sim.observe(left).unwrap();
+    let contact = &observation.contacts[0];
+    assert_eq!(contact.swarm, right);
+    assert!(contact.distance < 100.0);
+    // seen on the near side of the edge
+    assert!(contact.center.x < 0.0);
+    assert_eq!(sim.get_swarms_in_range(left).len(), 1);
+
+    sim.drain_events();
+    sim.step();
+    let locked = sim
+        .drain_events()
+        .any(|event| matches!(event.kind, EventKind::LockAcquired { swarm, .. } if swarm == left));
+    assert!(locked);
+}
+
+#[test]
+fn scenarios_enable_wrap_around() {
+    let scenario = Scenario::from_toml("[arena]\nwrap_around = true\n").unwrap();
+    assert!(scenario.build().bounds().wrap_around);
+
+    let source = "[arena]\nwrap_around = true\n\
+                  obstacles = [{ walls = { points = [[0.0, 0.0], [100.0, 0.0], [0.0, 100.0]] } }]\n";
+    let err = Scenario::from_toml(source).unwrap_err();
+    assert!(err.to_string().contains("wrap_around"), "{err}");
+}
@@ -0,0 +1,188 @@
+use glam::Vec2;
+use swarm_simulation::event::{DestroyCause, EventKind};
+use swarm_simulation::scenario::Scenario;
+use swarm_simulation::simulation::{Bounds, Simulation, SimulationConfig};
+use swarm_simulation::spawner::SpawnLocation;
+use swarm_simulation::zone::{Zone, ZoneConfig, ZoneShape, ZoneStep};
+
+fn zone_sim(shape: ZoneShape, damage: u32) -> Simulation {
+    Simulation::new(
+        SimulationConfig {
+            init_swarms: 0,
+            respawn_interval: 0,
+            zone: ZoneConfig {
+                shape: Some(shape),
+                schedule: vec![ZoneStep {
+                    tick: 0,
+                    scale: 0.2,
+                }],
+                damage_interval: 10,
+                damage,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Bounds::new(2000.0, 2000.0),
+    )
+}
+
+/// The arena corner farthest from the zone center
+fn far_corner(sim: &Simulation) -> Vec2 {
+    let center = sim.bounds().zone.unwrap().center();
+    Vec2::new(
+        if center.x < 1000.0 { 1900.0 } else { 100.0 },
+        if center.y < 1000.0 { 1900.0 } else { 100.0 },
+    )
+}
+
+#[test]
+fn zone_follows_the_schedule() {
+    let config = ZoneConfig {
+        schedule: vec![
+            ZoneStep {
+                tick: 100,
+                scale: 1.0,
+            },
+            ZoneStep {
+                tick: 300,
+                scale: 0.5,
+            },
+            ZoneStep {
+                tick: 400,
+                scale: 0.0,
+            },
+        ],
+        ..Default::default()
+    };
+    assert_eq!(config.scale_at(0), 1.0);
+    assert_eq!(config.scale_at(200), 0.75);
+    assert_eq!(config.scale_at(350), 0.25);
+    assert_eq!(config.scale_at(1000), 0.0);
+
+    let center = Vec2::new(300.0, 400.0);
+    let (min, max) = (Vec2::ZERO, Vec2::new(1000.0, 1000.0));
+    let full = Zone::new(ZoneShape::Rectangle, center, 1.0, min, max);
+    assert!(full.contains(min) && full.contains(max));
+    let half = Zone::new(ZoneShape::Rectangle, center, 0.5, min, max);
+    assert_eq!(
+        half,
+        Zone::Rectangle {
+            center,
+            half_size: Vec2::new(350.0, 300.0)
+        }
+    );
+    assert_eq!(
+        half.clamp_with_margin(Vec2::new(900.0, 0.0), 50.0),
+        Vec2::new(600.0, 150.0)
+    );
+    assert_eq!(half.raycast(center, Vec2::X), Some(350.0));
+    assert_eq!(half.raycast(Vec2::new(900.0, 400.0), Vec2::X), None);
+
+    let circle = Zone::new(ZoneShape::Circle, center, 1.0, min, max);
+    assert!(circle.contains(max));
+    assert_eq!(circle.raycast(center, Vec2::Y), Some(max.distance(center)));
+}
+
+#[test]
+fn ships_outside_the_zone_take_damage() {
+    let mut sim = zone_sim(ZoneShape::Circle, 3);
+    let outside = sim.spawn_swarm(far_corner(&sim), 4);
+    let inside = sim.spawn_swarm(sim.bounds().zone.unwrap().center(), 4);
+    sim.drain_events();
+
+    for _ in 0..11 {
+        sim.step();
+    }
+    let events: Vec<_> = sim.drain_events().collect();
+    let zone_damage = events
+        .iter()
+        .filter(|event| matches!(event.kind, EventKind::ZoneDamage { swarm, health_left: 0, .. } if swarm == outside))
+        .count();
+    assert_eq!(zone_damage, 4);
+    let zone_kills = events
+        .iter()
+        .filter(|event| matches!(event.kind, EventKind::ShipDestroyed { swarm, cause: DestroyCause::Zone, .. } if swarm == outside))
+        .count();
+    assert_eq!(zone_kills, 4);
+    assert!(events.iter().any(|event| event.kind
+        == EventKind::SwarmEliminated {
+            swarm: outside,
+            killer_swarm: None
+        }));
+    assert_eq!(sim.swarm(inside).unwrap().num_ships(), 4);
+}
+
+#[test]
+fn swarms_head_into_the_zone() {
+    for shape in [ZoneShape::Circle, ZoneShape::Rectangle] {
+        let mut sim = zone_sim(shape, 0);
+        let id = sim.spawn_swarm(far_corner(&sim), 6);
+        for _ in 0..1500 {
+            sim.step();
+        }
+        let zone = sim.bounds().zone.unwrap();
+        assert!(zone.contains(sim.swarm(id).unwrap().center), "{shape:?}");
+    }
+}
+
+#[test]
+fn swarms_spawn_inside_the_zone() {
+    for shape in [ZoneShape::Circle, ZoneShape::Rectangle] {
+        let mut sim = zone_sim(shape, 0);
+        for location in [
+            SpawnLocation::Interior { margin: 100.0 },
+            SpawnLocation::FarthestFromEnemies {
+                margin: 100.0,
+                candidates: 8,
+            },
+        ] {
+            for _ in 0..20 {
+                sim.spawn_random_swarm(location);
+            }
+        }
+        let zone = sim.bounds().zone.unwrap();
+        for swarm in sim.swarms() {
+            assert!(zone.contains(swarm.center), "{shape:?}: {}", swarm.center);
+        }
+    }
+}
+
+#[test]
+fn scenarios_configure_the_zone() {
+    let scenario = Scenario::from_toml(
+        "[simulation.zone]\nshape = \"rectangle\"\n\
+         schedule = [{ tick = 100, scale = 1.0 }, { tick = 200, scale = 0.5 }]\n",
+    )
+    .unwrap();
+    let mut sim = scenario.build();
+    assert!(matches!(sim.bounds().zone, Some(Zone::Rectangle { .. })));
+    for _ in 0..300 {
+        sim.step();
+    }
+    let Some(Zone::Rectangle { half_size, .. }) = sim.bounds().zone else {
+        panic!("zone is gone");
+    };
+    assert!(half_size.x < 1000.0);
+
+    for (source, expected) in [
+        (
+            "[arena]\nwrap_around = true\n[simulation.zone]\nshape = \"circle\"\n",
+            "wrap_around",
+        ),
+        (
+            "[simulation.zone]\nshape = \"circle\"\nschedule = [{ tick = 100, scale = 1.0 }, { tick = 50, scale = 0.5 }]\n",
+            "schedule[1].tick",
+        ),
+        (
+            "[simulation.zone]\nshape = \"circle\"\nschedule = [{ tick = 100, scale = 1.5 }]\n",
+            "schedule[0].scale",
+        ),
+        (
+            "[arena]\nwidth = 1000.0\nheight = 1000.0\n[simulation.zone]\nshape = \"circle\"\nmargin = 500.0\n",
+            "simulation.zone.margin",
+        ),
+    ] {
+        let err = Scenario::from_toml(source).unwrap_err();
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}